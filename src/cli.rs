@@ -1,9 +1,20 @@
-use argparse;
+use crate::trails::Trails;
 
 pub struct Options {
     pub level_name: String,
     pub show_description: bool,
     pub show_level_list: bool,
+    pub seed: Option<u64>,
+    pub record_path: Option<String>,
+    pub replay_path: Option<String>,
+    pub save_path: Option<String>,
+    pub load_path: Option<String>,
+    pub show_scores: bool,
+    pub scores_level_name: Option<String>,
+    pub player_name: String,
+    pub convert_bsd_path: Option<String>,
+    pub validate: bool,
+    pub trail_length: usize,
 }
 
 pub fn parse_args() -> Options {
@@ -14,6 +25,19 @@ pub fn parse_args() -> Options {
         level_name: "Default".to_string(),
         show_description: false,
         show_level_list: false,
+        seed: None,
+        record_path: None,
+        replay_path: None,
+        save_path: None,
+        load_path: None,
+        show_scores: false,
+        scores_level_name: None,
+        player_name: std::env::var("USER")
+            .or(std::env::var("USERNAME"))
+            .unwrap_or("anonymous".to_string()),
+        convert_bsd_path: None,
+        validate: false,
+        trail_length: Trails::DEFAULT_LENGTH,
     };
 
     // Set up argparser and use it
@@ -34,6 +58,61 @@ pub fn parse_args() -> Options {
             argparse::StoreTrue,
             "Show the description of a level",
         );
+        parser.refer(&mut options.seed).add_option(
+            &["-s", "--seed"],
+            argparse::StoreOption,
+            "Seed for the random number generator. Use the same seed to get the same planes",
+        );
+        parser.refer(&mut options.record_path).add_option(
+            &["-r", "--record"],
+            argparse::StoreOption,
+            "Record the game to a replay file",
+        );
+        parser.refer(&mut options.replay_path).add_option(
+            &["--replay"],
+            argparse::StoreOption,
+            "Watch a replay file recorded with --record",
+        );
+        parser.refer(&mut options.save_path).add_option(
+            &["--save-file"],
+            argparse::StoreOption,
+            "File that the game is saved to when F2 is pressed",
+        );
+        parser.refer(&mut options.load_path).add_option(
+            &["--load"],
+            argparse::StoreOption,
+            "Resume a game saved with F2",
+        );
+        parser.refer(&mut options.show_scores).add_option(
+            &["--scores"],
+            argparse::StoreTrue,
+            "Show the high scores for every scenario",
+        );
+        parser.refer(&mut options.scores_level_name).add_option(
+            &["--level-scores"],
+            argparse::StoreOption,
+            "Show the high scores for one scenario",
+        );
+        parser.refer(&mut options.player_name).add_option(
+            &["-n", "--name"],
+            argparse::Store,
+            "Name to put in the high score table. Defaults to your username",
+        );
+        parser.refer(&mut options.convert_bsd_path).add_option(
+            &["--convert-bsd"],
+            argparse::StoreOption,
+            "Convert a scenario file from BSD atc into a level file and print it",
+        );
+        parser.refer(&mut options.validate).add_option(
+            &["--validate"],
+            argparse::StoreTrue,
+            "Check a scenario for mistakes. The scenario can be a name or the path of a level file",
+        );
+        parser.refer(&mut options.trail_length).add_option(
+            &["--trail-length"],
+            argparse::Store,
+            "Number of previous positions shown behind each plane. Defaults to 3",
+        );
         parser.parse_args_or_exit();
     }
     options
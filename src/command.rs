@@ -1,42 +1,211 @@
+use std::fmt;
+
 use crate::direction::Direction;
-use crate::locations;
+use crate::locations::LocationId;
 use crate::plane;
+use crate::point::Point;
+
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum CommandType {
     ChangeAltitude,
     ChangeVisibility,
     Directional,
 }
 
-pub enum Command<'cmd> {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Command {
     ChangeAltitude(ChangeAltitudeCommand),
     ChangeVisibility(ChangeVisibilityCommand),
-    Directional(DirectionalCommand<'cmd>),
+    Directional(DirectionalCommand),
 }
 
-pub enum ChangeAltitudeCommand {
+impl Command {
+    pub fn command_type(&self) -> CommandType {
+        match self {
+            Command::ChangeAltitude(_) => CommandType::ChangeAltitude,
+            Command::Directional(_) => CommandType::Directional,
+            Command::ChangeVisibility(_) => CommandType::ChangeVisibility,
+        }
+    }
+
+    pub fn explain(&self) -> String {
+        // Readable description of what the command does, like "turn to 90° at beacon 3"
+        let side = |to_right: &bool| if *to_right { "right" } else { "left" };
+        let action = match self {
+            Command::ChangeAltitude(altitude_command) => match altitude_command.value {
+                ChangeAltitudeCommandValue::Absolute(altitude) => {
+                    format!("change altitude to {altitude}")
+                }
+                ChangeAltitudeCommandValue::Climb(amount) => format!("climb by {amount}"),
+                ChangeAltitudeCommandValue::Descend(amount) => format!("descend by {amount}"),
+            },
+            Command::ChangeVisibility(visibility_command) => {
+                match visibility_command.new_visibility {
+                    plane::PlaneVisibility::Marked => "mark".to_string(),
+                    plane::PlaneVisibility::Unmarked => "unmark".to_string(),
+                    plane::PlaneVisibility::Ignored => "ignore".to_string(),
+                }
+            }
+            Command::Directional(directional) => match &directional.value {
+                DirectionalCommandValue::AbsoluteTurn(direction) => {
+                    format!("turn to {}°", direction.to_heading())
+                }
+                DirectionalCommandValue::SoftTurn { to_right } => {
+                    format!("turn {}", side(to_right))
+                }
+                DirectionalCommandValue::HardTurn { to_right } => {
+                    format!("turn hard {}", side(to_right))
+                }
+                DirectionalCommandValue::TurnTowards(location) => {
+                    format!("turn towards {}", location.name())
+                }
+                DirectionalCommandValue::Circle { to_right } => {
+                    format!("circle {}", side(to_right))
+                }
+                DirectionalCommandValue::Route(waypoints) => {
+                    let names: Vec<_> = waypoints.iter().map(|w| w.name()).collect();
+                    format!("fly via {}", names.join(", "))
+                }
+            },
+        };
+        match self.temporality() {
+            CommandTemporality::Immediate => action,
+            CommandTemporality::Delayed(trigger) => format!("{action} at {}", trigger.explain()),
+        }
+    }
+
+    pub fn temporality(&self) -> &CommandTemporality {
+        match self {
+            Command::ChangeAltitude(altitude_command) => &altitude_command.temporality,
+            Command::Directional(directional_command) => &directional_command.temporality,
+            Command::ChangeVisibility(visibility_command) => &visibility_command.temporality,
+        }
+    }
+}
+
+// Short description of a command, as shown in the plane list
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::ChangeAltitude(altitude_command) => {
+                match altitude_command.value {
+                    ChangeAltitudeCommandValue::Absolute(altitude) => write!(f, "Alt {altitude}")?,
+                    ChangeAltitudeCommandValue::Climb(amount) => write!(f, "Alt +{amount}")?,
+                    ChangeAltitudeCommandValue::Descend(amount) => write!(f, "Alt -{amount}")?,
+                };
+                write!(f, "{}", altitude_command.temporality)
+            }
+            Command::ChangeVisibility(visibility_command) => {
+                match visibility_command.new_visibility {
+                    plane::PlaneVisibility::Marked => write!(f, "Mark")?,
+                    plane::PlaneVisibility::Unmarked => write!(f, "Unmark")?,
+                    plane::PlaneVisibility::Ignored => write!(f, "Ignore")?,
+                };
+                write!(f, "{}", visibility_command.temporality)
+            }
+            Command::Directional(directional) => {
+                let side_char = |to_right: &bool| if *to_right { 'R' } else { 'L' };
+                match &directional.value {
+                    DirectionalCommandValue::AbsoluteTurn(direction) => {
+                        write!(f, "{}", direction.to_heading())?
+                    }
+                    DirectionalCommandValue::Circle { to_right } => {
+                        write!(f, "Circ {}", side_char(to_right))?
+                    }
+                    DirectionalCommandValue::HardTurn { to_right } => {
+                        write!(f, "Hard {}", side_char(to_right))?
+                    }
+                    DirectionalCommandValue::SoftTurn { to_right } => {
+                        write!(f, "Soft {}", side_char(to_right))?
+                    }
+                    DirectionalCommandValue::TurnTowards(location) => write!(f, "{location}")?,
+                    DirectionalCommandValue::Route(waypoints) => {
+                        write!(f, "Rt")?;
+                        for waypoint in waypoints {
+                            write!(f, " {waypoint}")?;
+                        }
+                    }
+                };
+                write!(f, "{}", directional.temporality)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChangeAltitudeCommand {
+    pub value: ChangeAltitudeCommandValue,
+    pub temporality: CommandTemporality,
+}
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ChangeAltitudeCommandValue {
     Absolute(i32),
     Climb(i32),
     Descend(i32),
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ChangeVisibilityCommand {
     pub new_visibility: plane::PlaneVisibility,
+    pub temporality: CommandTemporality,
 }
 
-pub struct DirectionalCommand<'cmd> {
-    pub value: DirectionalCommandValue<'cmd>,
-    pub temporality: CommandTemporality<'cmd>,
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DirectionalCommand {
+    pub value: DirectionalCommandValue,
+    pub temporality: CommandTemporality,
 }
-pub enum DirectionalCommandValue<'cmd> {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DirectionalCommandValue {
     AbsoluteTurn(Direction),     // turn to a specific direction
     SoftTurn { to_right: bool }, // turn 45 deg
     HardTurn { to_right: bool }, // turn 90 deg
-    TurnTowards(&'cmd dyn locations::Location),
+    TurnTowards(LocationId),
     Circle { to_right: bool },
+    Route(Vec<LocationId>), // fly towards each location in turn. Locations are removed once they are reached
 }
-pub enum CommandTemporality<'cmd> {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum CommandTemporality {
     Immediate,
-    Delayed(&'cmd locations::Beacon),
+    Delayed(Trigger),
+}
+
+// Suffix added to the description of a command
+impl fmt::Display for CommandTemporality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandTemporality::Immediate => Ok(()),
+            CommandTemporality::Delayed(trigger) => write!(f, " @ {trigger}"),
+        }
+    }
+}
+
+// What a plane has to do for a delayed command to run
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Trigger {
+    Location(LocationId), // fly over an airport, beacon or exit
+    Position(Point),
+    Altitude(i32),
+}
+
+impl Trigger {
+    pub fn explain(&self) -> String {
+        match self {
+            Trigger::Location(id) => id.name(),
+            Trigger::Position(position) => format!("{},{}", position.x, position.y),
+            Trigger::Altitude(altitude) => format!("altitude {altitude}"),
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trigger::Location(id) => write!(f, "{id}"),
+            Trigger::Position(position) => write!(f, "{},{}", position.x, position.y),
+            Trigger::Altitude(altitude) => write!(f, "Alt {altitude}"),
+        }
+    }
 }
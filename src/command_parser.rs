@@ -1,49 +1,303 @@
 use crate::command::*;
 use crate::direction::Direction;
 use crate::game::Game;
-use crate::locations::Location;
-use crate::plane::PlaneVisibility;
+use crate::locations::{Location, LocationId};
+use crate::plane::{Plane, PlaneVisibility};
+use crate::point::Point;
 
 // Conventions of the functions within this file:
 // - If their success is not guaranteed, then return Result<_, String>
 // - The String is an error message designed to be displayed to the user
 
-// First time really using lifetimes; I don't really know why it compiles but it does.
+pub struct ParsedCommand {
+    pub plane_name: char,
+    pub action: ParsedAction,
+}
+
+pub enum ParsedAction {
+    Give {
+        command: Command,
+        // Whether to add the command to the end of the plane's queue rather than replacing its current command
+        append: bool,
+    },
+    Cancel(Option<CommandType>), // None means cancel every pending command
+    Query,
+}
+
+impl ParsedCommand {
+    pub fn explain(&self) -> String {
+        // Readable description of the command, like "Plane B: turn to 90° at beacon 3"
+        format!("Plane {}: {}", self.plane_name, self.explain_action())
+    }
+
+    pub fn explain_action(&self) -> String {
+        match &self.action {
+            ParsedAction::Give { command, append } => {
+                let explanation = command.explain();
+                if *append {
+                    format!("then {explanation}")
+                } else {
+                    explanation
+                }
+            }
+            ParsedAction::Cancel(command_type) => match command_type {
+                None => "cancel all commands".to_string(),
+                Some(CommandType::Directional) => "cancel directional commands".to_string(),
+                Some(CommandType::ChangeAltitude) => "cancel altitude commands".to_string(),
+                Some(CommandType::ChangeVisibility) => "cancel visibility commands".to_string(),
+            },
+            ParsedAction::Query => "show details".to_string(),
+        }
+    }
+}
+
+pub enum CommandPreview {
+    Incomplete, // could become valid if more is typed
+    Invalid(String),
+    Valid(String),
+}
+
+pub fn preview_command(raw_command: &str, game: &Game) -> CommandPreview {
+    // Check a command while it is being typed, explaining it if it is valid
+    let results = match parse_commands(raw_command, game) {
+        Ok(results) => results,
+        Err(e) if e == unexpected_end_of_command() => return CommandPreview::Incomplete,
+        Err(e) => return CommandPreview::Invalid(e),
+    };
+    // Planes that are doing the same thing are explained together, to fit in the command window
+    let mut actions: Vec<(String, Vec<char>)> = vec![];
+    let mut errors = vec![];
+    for result in results {
+        match result {
+            Ok(parsed) => {
+                let action = parsed.explain_action();
+                match actions.iter_mut().find(|(a, _)| *a == action) {
+                    Some((_, plane_names)) => plane_names.push(parsed.plane_name),
+                    None => actions.push((action, vec![parsed.plane_name])),
+                }
+            }
+            Err(e) if e.ends_with(&unexpected_end_of_command()) => {
+                return CommandPreview::Incomplete
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    let mut explanations: Vec<_> = actions
+        .into_iter()
+        .map(|(action, plane_names)| match plane_names.as_slice() {
+            [plane_name] => format!("Plane {plane_name}: {action}"),
+            _ => {
+                let plane_names: Vec<_> = plane_names.iter().map(|n| n.to_string()).collect();
+                format!("Planes {}: {action}", plane_names.join(", "))
+            }
+        })
+        .collect();
+    if explanations.is_empty() {
+        CommandPreview::Invalid(errors.join("\n"))
+    } else {
+        explanations.extend(errors);
+        CommandPreview::Valid(explanations.join("\n"))
+    }
+}
+
+pub fn completions(raw_command: &str, game: &Game) -> Vec<String> {
+    // Things that could be typed next: plane names, then command letters, then locations.
+    // Candidates are only suggested if they would make a command that could be valid
+    let mut chars = raw_command.chars();
+    let candidates: Vec<String> = match (chars.next(), chars.as_str()) {
+        (None, _) => {
+            let mut names: Vec<_> = game.planes.iter().map(|p| p.name.to_string()).collect();
+            names.sort();
+            return names;
+        }
+        (Some(_), "") => "&,tcramuix?".chars().map(|c| c.to_string()).collect(),
+        (Some(_), _) if raw_command.ends_with(',') => {
+            let mut names: Vec<_> = game.planes.iter().map(|p| p.name.to_string()).collect();
+            names.sort();
+            names
+        }
+        (Some(_), "&") => "tcramui".chars().map(|c| c.to_string()).collect(),
+        _ => {
+            // Locations are only suggested if they complete a command, otherwise letters like 'a' are ambiguous
+            let airports = game.level.airports.iter().map(|a| a.id());
+            let beacons = game.level.beacons.iter().map(|b| b.id());
+            let exits = game.level.exits.iter().map(|e| e.id());
+            return airports
+                .chain(beacons)
+                .chain(exits)
+                .map(|id| id.to_string().to_ascii_lowercase())
+                .filter(|id| {
+                    let completed = format!("{raw_command}{id}");
+                    matches!(preview_command(&completed, game), CommandPreview::Valid(_))
+                })
+                .collect();
+        }
+    };
+    candidates
+        .into_iter()
+        .filter(|c| {
+            let completed = format!("{raw_command}{c}");
+            !matches!(
+                preview_command(&completed, game),
+                CommandPreview::Invalid(_)
+            )
+        })
+        .collect()
+}
 
-pub fn parse_command<'game, 'cmd: 'game>(
+pub fn parse_commands(
     raw_command: &str,
-    game: &'game Game<'cmd>,
-) -> Result<(Command<'cmd>, char), String> {
-    // Second return value is name of plane
+    game: &Game,
+) -> Result<Vec<Result<ParsedCommand, String>>, String> {
+    // Parse a command that may be for several planes, like 'a,b,c a5' or '*a9' (* means every flying plane).
+    // The outer error is for when the command can't be understood at all,
+    // and the inner ones are for when it can't be given to a particular plane
+    let (plane_names, rest): (Vec<char>, &str) = if let Some(rest) = raw_command.strip_prefix('*') {
+        let mut plane_names: Vec<_> = game
+            .planes
+            .iter()
+            .filter(|p| !p.is_at_airport())
+            .map(|p| p.name)
+            .collect();
+        if plane_names.is_empty() {
+            Err("No planes are flying".to_string())?;
+        }
+        plane_names.sort();
+        (plane_names, rest.trim_start())
+    } else if raw_command.chars().nth(1) == Some(',') {
+        // No command has a ',' straight after the plane name, so this can't be mistaken for one
+        parse_plane_list(raw_command)?
+    } else {
+        return Ok(vec![parse_command(raw_command, game)]);
+    };
+    if rest.is_empty() {
+        unexpected_end_of_command_err()?
+    }
+
+    let results = plane_names
+        .into_iter()
+        .map(|plane_name| {
+            parse_command(&format!("{plane_name}{rest}"), game).map_err(|e| {
+                if e.starts_with(&format!("Plane {plane_name}")) {
+                    e
+                } else {
+                    format!("Plane {plane_name}: {e}")
+                }
+            })
+        })
+        .collect();
+    Ok(results)
+}
+
+fn parse_plane_list(raw_command: &str) -> Result<(Vec<char>, &str), String> {
+    // Split something like 'a,b,c a5' into the plane names and the command that follows them
+    let mut plane_names = vec![];
+    let mut rest = raw_command;
+    loop {
+        let mut chars = rest.chars();
+        let plane_name = chars.next().ok_or(unexpected_end_of_command())?;
+        if !plane_name.is_ascii_lowercase() {
+            Err(format!("Invalid plane name '{plane_name}'"))?;
+        }
+        plane_names.push(plane_name);
+        rest = chars.as_str();
+        match rest.strip_prefix(',') {
+            Some(after_comma) => rest = after_comma,
+            None => break,
+        }
+    }
+    Ok((plane_names, rest.trim_start()))
+}
 
+pub fn parse_command(raw_command: &str, game: &Game) -> Result<ParsedCommand, String> {
     let mut chars = raw_command.chars();
     let plane_name = chars.next().ok_or("Empty command".to_string())?;
+    let plane = game
+        .get_plane_by_name(plane_name)
+        .ok_or(format!("Plane {plane_name} does not exist"))?;
 
-    let command = match chars.next().ok_or(unexpected_end_of_command())? {
-        't' => create_turn_command(&raw_command[2..], game),
-        'c' => create_circle_command(&raw_command[2..], game),
-        'a' => create_change_altitude_command(&raw_command[2..]),
-        'm' => create_change_visiblity_command(PlaneVisibility::Marked),
-        'u' => create_change_visiblity_command(PlaneVisibility::Unmarked),
-        'i' => create_change_visiblity_command(PlaneVisibility::Ignored),
-        other => Err(format!("Unknown command '{other}'"))?,
+    // A '&' after the plane name means append
+    let raw_command = &raw_command[plane_name.len_utf8()..];
+    let (append, raw_command) = match raw_command.strip_prefix('&') {
+        Some(rest) => (true, rest),
+        None => (false, raw_command),
     };
+    let command_char = raw_command
+        .chars()
+        .next()
+        .ok_or(unexpected_end_of_command())?;
+    let rest = &raw_command[command_char.len_utf8()..];
+
+    let command = match command_char {
+        'x' | '?' if append => Err("Only commands can be added to the queue".to_string())?,
+        'x' => {
+            return Ok(ParsedCommand {
+                plane_name,
+                action: ParsedAction::Cancel(parse_cancel_type(rest)?),
+            })
+        }
+        '?' if !rest.is_empty() => Err(format!("Unexpected '{rest}' after query"))?,
+        '?' => {
+            return Ok(ParsedCommand {
+                plane_name,
+                action: ParsedAction::Query,
+            })
+        }
+        't' => create_turn_command(rest, game),
+        'c' => create_circle_command(rest, game),
+        'r' => create_route_command(rest, game),
+        'a' => create_change_altitude_command(rest, game),
+        'm' => create_change_visiblity_command(PlaneVisibility::Marked, rest, game),
+        'u' => create_change_visiblity_command(PlaneVisibility::Unmarked, rest, game),
+        'i' => create_change_visiblity_command(PlaneVisibility::Ignored, rest, game),
+        other => Err(format!("Unknown command '{other}'"))?,
+    }?;
+    check_trigger_is_reachable(&command, append, plane, game)?;
+
+    // Planes at an airport only turn once they have taken off, so only queued commands can wait for that
+    let is_immediate_turn = command.command_type() == CommandType::Directional
+        && matches!(command.temporality(), CommandTemporality::Immediate);
+    let will_take_off = append && plane.has_pending_command(CommandType::ChangeAltitude);
+    if plane.is_at_airport() && is_immediate_turn && !will_take_off {
+        Err(format!(
+            "Plane {} can't turn while waiting at an airport",
+            plane.name
+        ))?;
+    }
 
-    Ok((command?, plane_name))
+    Ok(ParsedCommand {
+        plane_name,
+        action: ParsedAction::Give { command, append },
+    })
 }
 
-fn create_change_visiblity_command<'cmd>(
+fn parse_cancel_type(raw_command: &str) -> Result<Option<CommandType>, String> {
+    // The type of command to cancel is given by the letter of any command of that type
+    let extra: String = raw_command.chars().skip(1).collect();
+    if !extra.is_empty() {
+        Err(format!("Unexpected '{extra}' after cancel"))?;
+    }
+    match raw_command.chars().next() {
+        None => Ok(None),
+        Some('t' | 'c' | 'r') => Ok(Some(CommandType::Directional)),
+        Some('a') => Ok(Some(CommandType::ChangeAltitude)),
+        Some('m' | 'u' | 'i') => Ok(Some(CommandType::ChangeVisibility)),
+        Some(other) => Err(format!("Unknown command '{other}'")),
+    }
+}
+
+fn create_change_visiblity_command(
     new_visibility: PlaneVisibility,
-) -> Result<Command<'cmd>, String> {
+    raw_command: &str,
+    game: &Game,
+) -> Result<Command, String> {
     Ok(Command::ChangeVisibility(ChangeVisibilityCommand {
         new_visibility,
+        temporality: determine_command_temporality(raw_command, game)?,
     }))
 }
 
-fn create_circle_command<'game, 'cmd>(
-    raw_command: &str,
-    game: &'game Game<'cmd>,
-) -> Result<Command<'cmd>, String> {
+fn create_circle_command(raw_command: &str, game: &Game) -> Result<Command, String> {
     let (to_right, next_index) = match raw_command.chars().next() {
         Some(c) => {
             let to_right = match c {
@@ -55,17 +309,13 @@ fn create_circle_command<'game, 'cmd>(
         }
         None => (true, 0), // (turn to right by default)
     };
-    // todo: check if delayed position is in flight path
     Ok(Command::Directional(DirectionalCommand {
         value: DirectionalCommandValue::Circle { to_right },
         temporality: determine_command_temporality(&raw_command[next_index..], game)?,
     }))
 }
 
-fn create_turn_command<'game, 'cmd: 'game>(
-    raw_command: &str,
-    game: &'game Game<'cmd>,
-) -> Result<Command<'cmd>, String> {
+fn create_turn_command(raw_command: &str, game: &Game) -> Result<Command, String> {
     let next_char = raw_command
         .chars()
         .next()
@@ -83,20 +333,33 @@ fn create_turn_command<'game, 'cmd: 'game>(
                 let location = parse_location_subcommand(&raw_command[1..], game)?;
                 (DirectionalCommandValue::TurnTowards(location), 3)
             }
-            _ => Err("You stuffed up!".to_string())?,
+            other => Err(format!("Unknown turn '{other}'"))?,
         },
     };
-    // todo: check if delayed position is in flight path
     Ok(Command::Directional(DirectionalCommand {
         value,
         temporality: determine_command_temporality(&raw_command[next_index..], game)?,
     }))
 }
 
-fn parse_location_subcommand<'game, 'cmd: 'game>(
-    raw_command: &str,
-    game: &'game Game<'cmd>,
-) -> Result<&'cmd dyn Location, String> {
+fn create_route_command(raw_command: &str, game: &Game) -> Result<Command, String> {
+    // Parse a list of locations like 'b1 b4 a2' (the spaces are optional)
+    let mut waypoints = vec![];
+    let mut rest = raw_command.trim_start();
+    while let Some('a' | 'b' | 'e') = rest.chars().next() {
+        waypoints.push(parse_location_subcommand(rest, game)?);
+        rest = rest[2..].trim_start();
+    }
+    if waypoints.is_empty() {
+        unexpected_end_of_command_err()?
+    }
+    Ok(Command::Directional(DirectionalCommand {
+        value: DirectionalCommandValue::Route(waypoints),
+        temporality: determine_command_temporality(rest, game)?,
+    }))
+}
+
+fn parse_location_subcommand(raw_command: &str, game: &Game) -> Result<LocationId, String> {
     // Parse something like 'b1' to be beacon 1
     let mut chars = raw_command.chars();
     let location_type = chars.next().ok_or(unexpected_end_of_command())?;
@@ -139,31 +402,34 @@ fn parse_location_subcommand<'game, 'cmd: 'game>(
         }
         other => Err(format!("Unexpected location type {other}"))?,
     };
-    Ok(location)
+    Ok(location.id())
 }
 
-fn create_change_altitude_command<'game>(raw_command: &str) -> Result<Command<'game>, String> {
+fn create_change_altitude_command(raw_command: &str, game: &Game) -> Result<Command, String> {
     let mut chars = raw_command.chars();
-    let cmd = match chars.next() {
+    let (value, next_index) = match chars.next() {
         Some(c) => match c {
             '0'..='9' => {
                 let alt = char_to_altitude(c);
-                Command::ChangeAltitude(ChangeAltitudeCommand::Absolute(alt?))
+                (ChangeAltitudeCommandValue::Absolute(alt?), 1)
             }
             'c' | '+' => {
                 let alt = char_to_altitude(chars.next().ok_or(unexpected_end_of_command())?)?;
-                Command::ChangeAltitude(ChangeAltitudeCommand::Climb(alt))
+                (ChangeAltitudeCommandValue::Climb(alt), 2)
             }
             'd' | '-' => {
                 let alt = char_to_altitude(chars.next().ok_or(unexpected_end_of_command())?)?;
-                Command::ChangeAltitude(ChangeAltitudeCommand::Descend(alt))
+                (ChangeAltitudeCommandValue::Descend(alt), 2)
             }
-            _ => Err("You messed up!".to_string())?,
+            other => Err(format!("Invalid altitude {other}"))?,
         },
         None => unexpected_end_of_command_err()?,
     };
 
-    Ok(cmd)
+    Ok(Command::ChangeAltitude(ChangeAltitudeCommand {
+        value,
+        temporality: determine_command_temporality(&raw_command[next_index..], game)?,
+    }))
 }
 
 fn char_to_altitude(c: char) -> Result<i32, String> {
@@ -184,30 +450,122 @@ fn char_to_int(c: char) -> Option<i32> {
     }
 }
 
-fn determine_command_temporality<'game, 'cmd: 'game>(
+fn determine_command_temporality(
     raw_command: &str,
-    game: &'game Game<'cmd>,
-) -> Result<CommandTemporality<'cmd>, String> {
+    game: &Game,
+) -> Result<CommandTemporality, String> {
+    // The first char separates the command from the trigger and can be anything
     let mut chars = raw_command.chars();
     match chars.next() {
-        Some(_) => {
-            if chars.next().is_none() {
-                unexpected_end_of_command_err()?
+        Some(separator) => {
+            let raw_trigger = &raw_command[separator.len_utf8()..];
+            let trigger = match raw_trigger
+                .chars()
+                .next()
+                .ok_or(unexpected_end_of_command())?
+            {
+                'h' => {
+                    let c = raw_trigger
+                        .chars()
+                        .nth(1)
+                        .ok_or(unexpected_end_of_command())?;
+                    Trigger::Altitude(char_to_altitude(c)?)
+                }
+                '0'..='9' => Trigger::Position(parse_coordinates(raw_trigger, game)?),
+                _ => Trigger::Location(parse_location_subcommand(raw_trigger, game)?),
+            };
+            Ok(CommandTemporality::Delayed(trigger))
+        }
+        None => Ok(CommandTemporality::Immediate),
+    }
+}
+
+fn parse_coordinates(raw_command: &str, game: &Game) -> Result<Point, String> {
+    // Parse something like '12,7' to be the point (12, 7)
+    let (raw_x, raw_y) = raw_command
+        .split_once(',')
+        .ok_or(unexpected_end_of_command())?;
+    if raw_y.is_empty() {
+        unexpected_end_of_command_err()?
+    }
+    let x = raw_x
+        .parse()
+        .map_err(|_| format!("Invalid coordinate {raw_x}"))?;
+    let y = raw_y
+        .parse()
+        .map_err(|_| format!("Invalid coordinate {raw_y}"))?;
+    if x < 0 || y < 0 || x >= game.level.size.x || y >= game.level.size.y {
+        Err(format!("Position {x},{y} is outside of the level"))?;
+    }
+    Ok(Point::new(x, y))
+}
+
+fn check_trigger_is_reachable(
+    command: &Command,
+    append: bool,
+    plane: &Plane,
+    game: &Game,
+) -> Result<(), String> {
+    // Reject delayed commands that would never run.
+    // If a pending command will change the plane's heading or altitude then we can't know where it will go,
+    // unless this command replaces it
+    let is_known = |command_type| {
+        !plane.has_pending_command(command_type)
+            || (!append && command.command_type() == command_type)
+    };
+    match command.temporality() {
+        CommandTemporality::Immediate => Ok(()),
+        CommandTemporality::Delayed(Trigger::Location(id)) => {
+            let position = match game.level.get_location(*id) {
+                Some(location) => location.get_position(),
+                None => return Ok(()),
+            };
+            if is_known(CommandType::Directional) && !is_in_flight_path(plane, game, &position) {
+                Err(format!(
+                    "Plane {} will not reach {id} on its current heading",
+                    plane.name
+                ))?;
             }
-            let beacon_name = chars.next().ok_or(unexpected_end_of_command())?.to_string();
-            let beacon = game
-                .level
-                .beacons
-                .iter()
-                .filter(|x| x.number.to_string() == beacon_name)
-                .next();
-            match beacon {
-                Some(b) => Ok(CommandTemporality::Delayed(b)),
-                None => Err(format!("Beacon {beacon_name} does not exist")),
+            Ok(())
+        }
+        CommandTemporality::Delayed(Trigger::Position(position)) => {
+            if is_known(CommandType::Directional) && !is_in_flight_path(plane, game, position) {
+                Err(format!(
+                    "Plane {} will not reach {},{} on its current heading",
+                    plane.name, position.x, position.y
+                ))?;
             }
+            Ok(())
         }
-        None => Ok(CommandTemporality::Immediate),
+        CommandTemporality::Delayed(Trigger::Altitude(altitude)) => {
+            let lowest = plane.altitude.min(plane.target_altitude);
+            let highest = plane.altitude.max(plane.target_altitude);
+            if is_known(CommandType::ChangeAltitude) && !(lowest..=highest).contains(altitude) {
+                Err(format!(
+                    "Plane {} will not reach altitude {altitude} without changing altitude first",
+                    plane.name
+                ))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn is_in_flight_path(plane: &Plane, game: &Game, position: &Point) -> bool {
+    // Whether the plane will pass over a position if it keeps flying straight until it leaves the level
+    let offset = plane.direction.to_point_offset();
+    let mut current = plane.position;
+    while current.x >= 0
+        && current.y >= 0
+        && current.x < game.level.size.x
+        && current.y < game.level.size.y
+    {
+        if current.equals(position) {
+            return true;
+        }
+        current = current.add(&offset);
     }
+    false
 }
 
 fn unexpected_end_of_command() -> String {
@@ -217,3 +575,231 @@ fn unexpected_end_of_command() -> String {
 fn unexpected_end_of_command_err<T>() -> Result<T, String> {
     Err(unexpected_end_of_command())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::tests::{flying_plane, game_with_planes};
+
+    fn test_game() -> Game {
+        // Default level with plane a flying east along the airway through beacon 0
+        game_with_planes(vec![flying_plane(
+            'a',
+            Point::new(5, 7),
+            Direction::East,
+            7,
+        )])
+    }
+
+    #[test]
+    fn rejects_unreachable_beacons() {
+        let game = test_game();
+        assert!(parse_command("atw@b0", &game).is_ok());
+        assert_eq!(
+            parse_command("atw@b1", &game).err(),
+            Some("Plane a will not reach B1 on its current heading".to_string())
+        );
+    }
+
+    #[test]
+    fn does_not_check_triggers_after_queued_turns() {
+        let mut game = test_game();
+        assert!(parse_command("a&tw@b1", &game).is_err());
+        game.apply_command("ate@b0").unwrap();
+        // Plane a will turn at beacon 0 first, so where it goes afterwards can't be known
+        assert!(parse_command("a&tw@b1", &game).is_ok());
+        // Without '&' the turn at beacon 0 is replaced, so it keeps flying east
+        assert!(parse_command("atw@b1", &game).is_err());
+    }
+
+    #[test]
+    fn checks_triggers_of_every_command_type() {
+        let mut game = test_game();
+        assert!(parse_command("aa2@b0", &game).is_ok());
+        assert!(parse_command("ai@b0", &game).is_ok());
+        assert_eq!(
+            parse_command("aa2@b1", &game).err(),
+            Some("Plane a will not reach B1 on its current heading".to_string())
+        );
+        assert!(parse_command("ai@b1", &game).is_err());
+        // An altitude command doesn't replace a pending turn, so the heading is unknown
+        game.apply_command("ate@b0").unwrap();
+        assert!(parse_command("aa2@b1", &game).is_ok());
+    }
+
+    #[test]
+    fn checks_location_position_and_altitude_triggers() {
+        let mut game = test_game();
+        assert!(parse_command("atw@e2", &game).is_ok());
+        assert_eq!(
+            parse_command("atw@a0", &game).err(),
+            Some("Plane a will not reach A0 on its current heading".to_string())
+        );
+        assert!(parse_command("atw@20,7", &game).is_ok());
+        assert_eq!(
+            parse_command("atw@20,8", &game).err(),
+            Some("Plane a will not reach 20,8 on its current heading".to_string())
+        );
+        // Plane a is already at altitude 7
+        assert!(parse_command("atw@h7", &game).is_ok());
+        assert_eq!(
+            parse_command("atw@h3", &game).err(),
+            Some("Plane a will not reach altitude 3 without changing altitude first".to_string())
+        );
+        game.apply_command("aa2").unwrap();
+        assert!(parse_command("atw@h3", &game).is_ok());
+    }
+
+    #[test]
+    fn rejects_positions_outside_level() {
+        let game = test_game();
+        assert_eq!(
+            parse_command("atw@1,-5", &game).err(),
+            Some("Position 1,-5 is outside of the level".to_string())
+        );
+        assert_eq!(
+            parse_command("atw@30,7", &game).err(),
+            Some("Position 30,7 is outside of the level".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_cancel_and_query() {
+        let game = test_game();
+        assert!(matches!(
+            parse_command("ax", &game).map(|parsed| parsed.action),
+            Ok(ParsedAction::Cancel(None))
+        ));
+        assert!(matches!(
+            parse_command("axa", &game).map(|parsed| parsed.action),
+            Ok(ParsedAction::Cancel(Some(CommandType::ChangeAltitude)))
+        ));
+        assert!(matches!(
+            parse_command("a?", &game).map(|parsed| parsed.action),
+            Ok(ParsedAction::Query)
+        ));
+    }
+
+    #[test]
+    fn rejects_text_after_query_and_cancel() {
+        let game = test_game();
+        assert_eq!(
+            parse_command("a?x", &game).err(),
+            Some("Unexpected 'x' after query".to_string())
+        );
+        assert_eq!(
+            parse_command("axaaa", &game).err(),
+            Some("Unexpected 'aa' after cancel".to_string())
+        );
+        assert_eq!(
+            parse_command("a&x", &game).err(),
+            Some("Only commands can be added to the queue".to_string())
+        );
+    }
+
+    #[test]
+    fn cancel_removes_pending_commands() {
+        let mut game = test_game();
+        game.apply_command("aa2").unwrap();
+        game.apply_command("a&te@b0").unwrap();
+        game.apply_command("axa").unwrap();
+        let plane = game.get_plane_by_name('a').unwrap();
+        assert!(!plane.has_pending_command(CommandType::ChangeAltitude));
+        assert!(plane.has_pending_command(CommandType::Directional));
+    }
+
+    fn game_with_planes_a_c_and_r() -> Game {
+        // Plane names that are also command letters, so that commands for one plane look like lists
+        game_with_planes(vec![
+            flying_plane('a', Point::new(5, 7), Direction::East, 7),
+            flying_plane('c', Point::new(5, 10), Direction::East, 7),
+            flying_plane('r', Point::new(5, 13), Direction::East, 7),
+        ])
+    }
+
+    fn has_pending_command(game: &Game, plane_name: char, command_type: CommandType) -> bool {
+        let plane = game.get_plane_by_name(plane_name).unwrap();
+        plane.has_pending_command(command_type)
+    }
+
+    #[test]
+    fn command_letters_are_not_plane_lists() {
+        let mut game = game_with_planes_a_c_and_r();
+        assert!(matches!(
+            preview_command("ar a0", &game),
+            CommandPreview::Valid(explanation) if explanation.starts_with("Plane a:")
+        ));
+        game.apply_command("ar a0").unwrap();
+        assert!(has_pending_command(&game, 'a', CommandType::Directional));
+        assert!(!has_pending_command(&game, 'r', CommandType::Directional));
+        assert!(!has_pending_command(
+            &game,
+            'a',
+            CommandType::ChangeAltitude
+        ));
+        assert!(!has_pending_command(
+            &game,
+            'r',
+            CommandType::ChangeAltitude
+        ));
+
+        // A circle for plane a, with a space where the direction should be
+        assert!(game.apply_command("ac a0").is_err());
+        assert!(!has_pending_command(
+            &game,
+            'c',
+            CommandType::ChangeAltitude
+        ));
+
+        assert_eq!(
+            parse_command("rt x", &game).err(),
+            Some("Unknown turn ' '".to_string())
+        );
+    }
+
+    #[test]
+    fn plane_list_gives_command_to_every_plane() {
+        let mut game = game_with_planes_a_c_and_r();
+        game.apply_command("a,r a0").unwrap();
+        game.apply_command("c,a,r a5").unwrap();
+        game.apply_command("c,ra4").unwrap();
+        for plane in &game.planes {
+            assert!(plane.has_pending_command(CommandType::ChangeAltitude));
+        }
+        assert_eq!(
+            game.apply_command("a,z a5"),
+            Ok(Some("Plane z does not exist".to_string()))
+        );
+        assert_eq!(
+            game.apply_command("y,z a5").err(),
+            Some("Plane y does not exist\nPlane z does not exist".to_string())
+        );
+    }
+
+    #[test]
+    fn unfinished_plane_list_is_incomplete() {
+        let game = game_with_planes_a_c_and_r();
+        for raw_command in ["a,", "a,r", "a,r ", "a,r,"] {
+            assert!(
+                matches!(
+                    preview_command(raw_command, &game),
+                    CommandPreview::Incomplete
+                ),
+                "{raw_command}"
+            );
+        }
+        assert!(matches!(
+            preview_command("a,,r a5", &game),
+            CommandPreview::Invalid(reason) if reason == "Invalid plane name ','"
+        ));
+    }
+
+    #[test]
+    fn rejects_commands_for_missing_planes() {
+        let game = test_game();
+        assert_eq!(
+            parse_command("ztw@b0", &game).err(),
+            Some("Plane z does not exist".to_string())
+        );
+    }
+}
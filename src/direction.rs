@@ -1,6 +1,8 @@
 use crate::point::Point;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Direction {
     North,
     NorthEast,
@@ -40,7 +42,7 @@ impl Direction {
         }
     }
 
-    pub fn to_char(&self) -> char {
+    pub fn to_char(self) -> char {
         match self {
             Direction::North => '^',
             Direction::NorthEast => '┐',
@@ -53,7 +55,7 @@ impl Direction {
         }
     }
 
-    pub fn to_point_offset(&self) -> Point {
+    pub fn to_point_offset(self) -> Point {
         match self {
             Direction::North => Point::new(0, -1),
             Direction::NorthEast => Point::new(1, -1),
@@ -66,7 +68,7 @@ impl Direction {
         }
     }
 
-    pub fn to_heading(&self) -> i32 {
+    pub fn to_heading(self) -> i32 {
         match self {
             Direction::North => 0,
             Direction::NorthEast => 45,
@@ -83,7 +85,7 @@ impl Direction {
         // Convert a heading into a direction.
         // If heading does not correspond to a direction then returns None
 
-        let modulo_heading = ((heading % 360) + 360) % 360;
+        let modulo_heading = heading.rem_euclid(360);
         match modulo_heading {
             0 => Some(Direction::North),
             45 => Some(Direction::NorthEast),
@@ -1,26 +1,70 @@
 use std::collections::HashMap;
 use std::fmt;
 
+use crate::command::CommandType;
+use crate::command_parser::{self, ParsedAction, ParsedCommand};
+use crate::game_event::GameEvent;
 use crate::levels::level::Level;
 use crate::locations::*;
 use crate::plane::*;
 
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-pub struct Game<'game> {
-    pub planes: Vec<Plane<'game>>,
-    pub level: &'game Level,
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Game {
+    pub planes: Vec<Plane>,
+    pub level: Level,
     pub ticks: i32,
     pub planes_safe: i32,
+    // Seed that the rng was created from, kept so that a game can be reproduced
+    pub seed: u64,
     // Map of entry number to when the previous plane was spawned there
     entry_last_spawn: HashMap<i32, i32>,
+    rng: ChaCha8Rng,
+    // Events that have happened since the last tick, such as the first plane being spawned
+    #[serde(skip)]
+    events: Vec<GameEvent>,
 }
 
+// Two planes that will collide if nothing changes
+pub struct Conflict {
+    pub plane_a: char,
+    pub plane_b: char,
+    pub ticks: i32, // how many ticks from now the planes will collide
+}
+
+#[allow(clippy::enum_variant_names)]
 pub enum LoseCondition {
-    PlaneCollision { plane_a: char, plane_b: char },
-    PlaneIllegallyExited { plane: char },
-    PlaneHitGround { plane: char },
-    PlaneRanOutOfFuel { plane: char },
+    PlaneCollision {
+        plane_a: char,
+        plane_b: char,
+    },
+    PlaneIllegallyExited {
+        plane: char,
+    },
+    PlaneHitGround {
+        plane: char,
+    },
+    PlaneRanOutOfFuel {
+        plane: char,
+    },
+    PlaneLandedAtWrongAirport {
+        plane: char,
+        airport: LocationId,
+        destination: LocationId,
+    },
+    PlaneExitedAtWrongExit {
+        plane: char,
+        exit: LocationId,
+        destination: LocationId,
+    },
+    PlaneExitedAtWrongAltitude {
+        plane: char,
+        exit: LocationId,
+        altitude: i32,
+    },
 }
 
 impl fmt::Display for LoseCondition {
@@ -38,30 +82,66 @@ impl fmt::Display for LoseCondition {
             LoseCondition::PlaneRanOutOfFuel { plane } => {
                 write!(f, "Plane {plane} ran out of fuel")
             }
+            LoseCondition::PlaneLandedAtWrongAirport {
+                plane,
+                airport,
+                destination,
+            } => {
+                write!(
+                    f,
+                    "Plane {plane} landed at {airport} but was going to {destination}"
+                )
+            }
+            LoseCondition::PlaneExitedAtWrongExit {
+                plane,
+                exit,
+                destination,
+            } => {
+                write!(
+                    f,
+                    "Plane {plane} exited at {exit} but was going to {destination}"
+                )
+            }
+            LoseCondition::PlaneExitedAtWrongAltitude {
+                plane,
+                exit,
+                altitude,
+            } => {
+                write!(
+                    f,
+                    "Plane {plane} exited at {exit} at altitude {altitude} instead of {}",
+                    Exit::EXIT_ALTITUDE
+                )
+            }
         }
     }
 }
 
-impl<'game> Game<'game> {
+impl Game {
     pub const MAX_PLANES: i32 = 20;
     pub const ENTRY_ALTITUDE: i32 = 7;
     pub const AIRPORT_ENTRY_ALTITUDE: i32 = 0;
     pub const LOW_FUEL_THRESHOLD: i32 = 15;
     pub const MIN_ENTRY_SPAWN_INTERVAL: i32 = 5;
+    pub const CONFLICT_LOOKAHEAD_TICKS: i32 = 6;
 
-    pub fn new(level: &'game mut Level) -> Self {
+    pub fn new(level: Level, seed: u64) -> Self {
         let mut g = Game {
             planes: vec![],
-            level: level,
+            level,
             ticks: 0,
             planes_safe: 0,
+            seed,
             entry_last_spawn: HashMap::new(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            events: vec![],
         };
         g.create_new_plane();
         g
     }
 
-    pub fn tick(&mut self) -> Result<(), LoseCondition> {
+    pub fn tick(&mut self) -> Result<Vec<GameEvent>, LoseCondition> {
+        // Returns what happened during the tick
         self.ticks += 1;
 
         self.maybe_create_new_plane();
@@ -69,75 +149,140 @@ impl<'game> Game<'game> {
         self.remove_safe_planes(); // remove safe before checking lose so that EG planes that just landed don't count as crashed
         self.check_lose_conditions()?;
 
-        Ok(())
+        Ok(std::mem::take(&mut self.events))
     }
 
-    fn maybe_create_new_plane(&mut self) {
-        if self.planes.len() < Self::MAX_PLANES as usize {
-            let mut rng = rand::thread_rng();
-            if rng.gen::<f64>() <= self.level.plane_spawn_chance {
-                self.create_new_plane();
+    pub fn apply_command(&mut self, raw_command: &str) -> Result<Option<String>, String> {
+        // Parse a command typed by the user and give it to the planes it is for.
+        // Returns a message to show the user if there is one,
+        // or an error message designed to be displayed to the user if the command is invalid for every plane.
+        // If it is only invalid for some planes then it is given to the rest and the errors are in the message
+        let results = command_parser::parse_commands(raw_command, self)?;
+        let plane_count = results.len();
+        let mut messages = vec![];
+        let mut errors = vec![];
+        for result in results {
+            match result.and_then(|parsed| self.apply_parsed_command(parsed)) {
+                Ok(Some(message)) => messages.push(message),
+                Ok(None) => (),
+                Err(e) => errors.push(e),
             }
         }
+        if errors.len() == plane_count {
+            return Err(errors.join("\n"));
+        }
+        messages.extend(errors);
+        if messages.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(messages.join("\n")))
+        }
+    }
+
+    fn apply_parsed_command(&mut self, parsed: ParsedCommand) -> Result<Option<String>, String> {
+        let plane = self
+            .get_plane_by_name_mut(parsed.plane_name)
+            .ok_or(format!("Plane {} does not exist", parsed.plane_name))?;
+        match parsed.action {
+            ParsedAction::Give { command, append } => {
+                if append {
+                    plane.queue_command(command);
+                } else {
+                    plane.add_command(command);
+                }
+                Ok(None)
+            }
+            ParsedAction::Cancel(command_type) => {
+                plane.cancel_commands(command_type);
+                let cancelled = match command_type {
+                    None => "all",
+                    Some(CommandType::Directional) => "directional",
+                    Some(CommandType::ChangeAltitude) => "altitude",
+                    Some(CommandType::ChangeVisibility) => "visibility",
+                };
+                Ok(Some(format!(
+                    "Cancelled {cancelled} commands for plane {}",
+                    plane.name
+                )))
+            }
+            ParsedAction::Query => Ok(Some(plane.describe())),
+        }
+    }
+
+    pub fn random_seed() -> u64 {
+        // Pick a seed for a game where the user has not specified one
+        rand::thread_rng().gen()
+    }
+
+    fn maybe_create_new_plane(&mut self) {
+        if self.planes.len() < Self::MAX_PLANES as usize
+            && self.rng.gen::<f64>() <= self.level.plane_spawn_chance
+        {
+            self.create_new_plane();
+        }
     }
 
     fn create_new_plane(&mut self) {
         // Randomly spawn a new plane
-        let mut rng = rand::thread_rng();
-
-        let available_takeoff_airports: Vec<_> = self.level.airports.iter().collect();
+        let available_takeoff_airports: Vec<_> =
+            self.level.airports.iter().map(|a| a.id()).collect();
 
         let mut available_entries: Vec<_> = self.level.exits.iter().collect();
         available_entries.retain(|e| {
             self.entry_last_spawn.get(&e.number).unwrap_or(&-1000) + Self::MIN_ENTRY_SPAWN_INTERVAL
                 < self.ticks
         });
-        let location = self.random_airport_or_exit(&available_takeoff_airports, &available_entries);
-
-        let mut available_landing_airports: Vec<_> = self.level.airports.iter().collect();
-        let mut available_exits: Vec<_> = self.level.exits.iter().collect();
-
-        let (direction, state, position, alt) = if location.0.is_some() {
-            // Airport case
-            let location: &'game Airport = location.0.unwrap();
-
-            // prevent plane destination being current location.
-
-            available_landing_airports.retain(|a| a.number != location.number);
-            (
-                location.flight_direction,
-                PlaneState::AtAirport(location),
-                location.position,
-                Self::AIRPORT_ENTRY_ALTITUDE,
-            )
+        let available_entries: Vec<_> = available_entries.iter().map(|e| e.id()).collect();
+        let location = if available_takeoff_airports.is_empty() && available_entries.is_empty() {
+            // Every entry has had a plane recently and there are no airports, so use any entry
+            self.any_random_airport_or_exit()
         } else {
-            // Exit case
-            let location = location.1.unwrap();
-
-            self.entry_last_spawn.insert(location.number, self.ticks);
+            self.random_airport_or_exit(&available_takeoff_airports, &available_entries)
+        };
 
-            // prevent plane destination being current location.
-            available_exits.retain(|e| e.number != location.number);
-            (
-                location.entry_direction,
-                PlaneState::Flying,
-                location.position,
-                Self::ENTRY_ALTITUDE,
-            )
+        let mut available_landing_airports: Vec<_> =
+            self.level.airports.iter().map(|a| a.id()).collect();
+        let mut available_exits: Vec<_> = self.level.exits.iter().map(|e| e.id()).collect();
+
+        // prevent plane destination being current location.
+        available_landing_airports.retain(|a| *a != location);
+        available_exits.retain(|e| *e != location);
+
+        let (direction, state, position, alt) = match location {
+            LocationId::Airport(number) => {
+                let airport = self.level.get_airport(number).unwrap();
+                (
+                    airport.flight_direction,
+                    PlaneState::AtAirport(location),
+                    airport.position,
+                    Self::AIRPORT_ENTRY_ALTITUDE,
+                )
+            }
+            LocationId::Exit(number) => {
+                let exit = self.level.get_exit(number).unwrap();
+                self.entry_last_spawn.insert(number, self.ticks);
+                (
+                    exit.entry_direction,
+                    PlaneState::Flying,
+                    exit.position,
+                    Self::ENTRY_ALTITUDE,
+                )
+            }
+            LocationId::Beacon(_) => panic!("Planes can't spawn at beacons"),
         };
 
-        let destination_tuple =
+        let destination =
             self.random_airport_or_exit(&available_landing_airports, &available_exits);
-        let destination: &dyn Location = if destination_tuple.0.is_some() {
-            destination_tuple.0.unwrap()
-        } else {
-            destination_tuple.1.unwrap()
-        };
 
         // Create plane
+        let name = self.next_free_plane_name();
+        self.events.push(GameEvent::PlaneSpawned {
+            plane: name,
+            location,
+        });
         self.planes.push(Plane {
-            name: self.next_free_plane_name(),
-            plane_type: if rng.gen::<f64>() < 0.5 {
+            name,
+            plane_type: if self.rng.gen::<f64>() < 0.5 {
                 PlaneType::Jet
             } else {
                 PlaneType::Propeller
@@ -160,21 +305,54 @@ impl<'game> Game<'game> {
 
     fn move_planes(&mut self) {
         for plane in &mut self.planes {
-            plane.fly();
+            let had_low_fuel = plane.remaining_fuel <= Self::LOW_FUEL_THRESHOLD;
+            plane.fly(&self.level, &mut self.events);
+            if !had_low_fuel && plane.remaining_fuel <= Self::LOW_FUEL_THRESHOLD {
+                self.events.push(GameEvent::LowFuel { plane: plane.name });
+            }
         }
     }
 
     fn remove_safe_planes(&mut self) {
         let old_len = self.planes.len();
-        self.planes.retain(|plane| !plane.is_at_destination());
+        let level = &self.level;
+        let events = &mut self.events;
+        self.planes.retain(|plane| {
+            if !plane.is_at_destination(level) {
+                return true;
+            }
+            events.push(match plane.destination {
+                LocationId::Exit(_) => GameEvent::PlaneExited {
+                    plane: plane.name,
+                    exit: plane.destination,
+                },
+                _ => GameEvent::PlaneLanded {
+                    plane: plane.name,
+                    airport: plane.destination,
+                },
+            });
+            false
+        });
         self.planes_safe += (old_len - self.planes.len()) as i32;
     }
 
     fn check_lose_conditions(&self) -> Result<(), LoseCondition> {
         for plane in &self.planes {
+            // Planes that reached their destination have already been removed,
+            // so a plane at an airport or exit that it could have used went to the wrong one
+
             // Check if plane has hit ground
             if plane.altitude == 0 && !plane.is_at_airport() {
-                Err(LoseCondition::PlaneHitGround { plane: plane.name })?;
+                match self.usable_location_at(plane) {
+                    Some(airport @ LocationId::Airport(_)) if airport != plane.destination => {
+                        Err(LoseCondition::PlaneLandedAtWrongAirport {
+                            plane: plane.name,
+                            airport,
+                            destination: plane.destination,
+                        })?
+                    }
+                    _ => Err(LoseCondition::PlaneHitGround { plane: plane.name })?,
+                }
             }
 
             let is_out_of_bounds = plane.position.x <= 0
@@ -183,7 +361,23 @@ impl<'game> Game<'game> {
                 || plane.position.y >= self.level.size.y - 1;
             if is_out_of_bounds && plane.ticks_since_created > 1 {
                 // (don't kill planes that have just entered because that makes no sense)
-                Err(LoseCondition::PlaneIllegallyExited { plane: plane.name })?;
+                match self.usable_location_at(plane) {
+                    Some(exit @ LocationId::Exit(_)) if exit != plane.destination => {
+                        Err(LoseCondition::PlaneExitedAtWrongExit {
+                            plane: plane.name,
+                            exit,
+                            destination: plane.destination,
+                        })?
+                    }
+                    Some(exit @ LocationId::Exit(_)) => {
+                        Err(LoseCondition::PlaneExitedAtWrongAltitude {
+                            plane: plane.name,
+                            exit,
+                            altitude: plane.altitude,
+                        })?
+                    }
+                    _ => Err(LoseCondition::PlaneIllegallyExited { plane: plane.name })?,
+                }
             }
 
             // Check if plane ran out of fuel
@@ -193,11 +387,7 @@ impl<'game> Game<'game> {
 
             // Check collisions between planes
             for plane_2 in &self.planes {
-                if !plane.is_at_airport()
-                    && !plane_2.is_at_airport()
-                    && plane.name != plane_2.name
-                    && plane.is_colliding_with(plane_2)
-                {
+                if plane.name != plane_2.name && plane.is_colliding_with(plane_2) {
                     Err(LoseCondition::PlaneCollision {
                         plane_a: plane.name,
                         plane_b: plane_2.name,
@@ -208,6 +398,19 @@ impl<'game> Game<'game> {
         Ok(())
     }
 
+    fn usable_location_at(&self, plane: &Plane) -> Option<LocationId> {
+        // Airport or exit that the plane is at and is flying in the right direction to use
+        let airports = self.level.airports.iter().map(|a| a as &dyn Location);
+        let exits = self.level.exits.iter().map(|e| e as &dyn Location);
+        airports
+            .chain(exits)
+            .find(|l| {
+                l.get_position().equals(&plane.position)
+                    && l.accessible_from_direction(&plane.direction)
+            })
+            .map(|l| l.id())
+    }
+
     fn next_free_plane_name(&self) -> char {
         let existing_plane_names: Vec<_> = self.planes.iter().map(|x| x.name).collect();
         for ch in b'a'..=b'z' {
@@ -218,43 +421,268 @@ impl<'game> Game<'game> {
         panic!("Could not find a spare char for plane names (this should not be possible)");
     }
 
-    #[allow(unused)]
+    pub fn predict_conflicts(&self, ticks: i32) -> Vec<Conflict> {
+        // Fly copies of the planes forward with their current commands to find which ones will collide,
+        // soonest first. Each pair is only listed once, with the soonest collision
+        let projections: Vec<_> = self
+            .planes
+            .iter()
+            .map(|plane| plane.project(&self.level, ticks))
+            .collect();
+        let mut conflicts = vec![];
+        for (idx_a, plane_a) in self.planes.iter().enumerate() {
+            for (idx_b, plane_b) in self.planes.iter().enumerate().skip(idx_a + 1) {
+                // (zip stops once either plane has reached its destination)
+                let collision_idx = projections[idx_a]
+                    .iter()
+                    .zip(&projections[idx_b])
+                    .position(|(a, b)| a.is_colliding_with(b));
+                if let Some(collision_idx) = collision_idx {
+                    conflicts.push(Conflict {
+                        plane_a: plane_a.name.min(plane_b.name),
+                        plane_b: plane_a.name.max(plane_b.name),
+                        ticks: collision_idx as i32 + 1,
+                    });
+                }
+            }
+        }
+        conflicts.sort_by_key(|c| c.ticks);
+        conflicts
+    }
+
     pub fn get_plane_by_name(&self, name: char) -> Option<&Plane> {
-        self.planes.iter().filter(|&p| p.name == name).next()
+        self.planes.iter().find(|&p| p.name == name)
     }
 
-    pub fn get_plane_by_name_mut(&mut self, name: char) -> Option<&mut Plane<'game>> {
-        self.planes.iter_mut().filter(|p| p.name == name).next()
+    pub fn get_plane_by_name_mut(&mut self, name: char) -> Option<&mut Plane> {
+        self.planes.iter_mut().find(|p| p.name == name)
     }
 
-    fn any_random_airport_or_exit(&self) -> (Option<&'game Airport>, Option<&'game Exit>) {
-        let mut rng = rand::thread_rng();
+    fn any_random_airport_or_exit(&mut self) -> LocationId {
         let num_possibilities = self.level.exits.len() + self.level.airports.len();
-        let spawn_point_idx: usize = rng.gen_range(0..num_possibilities);
+        let spawn_point_idx: usize = self.rng.gen_range(0..num_possibilities);
 
         if spawn_point_idx < self.level.exits.len() {
-            (None, Some(&self.level.exits[spawn_point_idx]))
+            self.level.exits[spawn_point_idx].id()
         } else {
-            (
-                Some(&self.level.airports[spawn_point_idx - self.level.exits.len()]),
-                None,
-            )
+            self.level.airports[spawn_point_idx - self.level.exits.len()].id()
         }
     }
 
     fn random_airport_or_exit(
-        &self,
-        airports: &Vec<&'game Airport>,
-        exits: &Vec<&'game Exit>,
-    ) -> (Option<&'game Airport>, Option<&'game Exit>) {
-        let mut rng = rand::thread_rng();
+        &mut self,
+        airports: &[LocationId],
+        exits: &[LocationId],
+    ) -> LocationId {
         let num_possibilities = exits.len() + airports.len();
-        let spawn_point_idx: usize = rng.gen_range(0..num_possibilities);
+        let spawn_point_idx: usize = self.rng.gen_range(0..num_possibilities);
 
         if spawn_point_idx < exits.len() {
-            (None, Some(&exits[spawn_point_idx]))
+            exits[spawn_point_idx]
         } else {
-            (Some(&airports[spawn_point_idx - exits.len()]), None)
+            airports[spawn_point_idx - exits.len()]
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::command::CommandType;
+    use crate::direction::Direction;
+    use crate::levels::default;
+    use crate::point::Point;
+
+    pub fn flying_plane(name: char, position: Point, direction: Direction, altitude: i32) -> Plane {
+        // A jet that moves every tick and has plenty of fuel
+        Plane {
+            name,
+            plane_type: PlaneType::Jet,
+            altitude,
+            target_altitude: altitude,
+            direction,
+            position,
+            state: PlaneState::Flying,
+            visibility: PlaneVisibility::Marked,
+            ticks_since_created: 10,
+            remaining_fuel: 50,
+            destination: LocationId::Exit(0),
+            command_queue: vec![],
+            command_map: HashMap::new(),
+        }
+    }
+
+    pub fn game_with_planes(planes: Vec<Plane>) -> Game {
+        // Game on the default level with only the given planes, and no new planes spawning
+        let mut game = Game::new(default::create(), 0);
+        game.level.plane_spawn_chance = 0.;
+        game.planes = planes;
+        // (the plane that spawned when the game was created has been replaced)
+        game.events.clear();
+        game
+    }
+
+    #[test]
+    fn runs_without_a_terminal() {
+        let mut game = Game::new(default::create(), 3);
+        // The first plane is spawned straight away
+        let plane_name = game.planes[0].name;
+        game.apply_command(&format!("{plane_name}a5")).unwrap();
+        let plane = game.get_plane_by_name(plane_name).unwrap();
+        assert!(plane.command_map.contains_key(&CommandType::ChangeAltitude));
+
+        for _ in 0..10 {
+            assert!(game.tick().is_ok());
+        }
+        assert_eq!(game.ticks, 10);
+    }
+
+    #[test]
+    fn rejects_commands_for_missing_planes() {
+        let mut game = Game::new(default::create(), 3);
+        assert_eq!(
+            game.apply_command("za5").err(),
+            Some("Plane z does not exist".to_string())
+        );
+    }
+
+    #[test]
+    fn landing_at_the_wrong_airport_loses() {
+        // Airport 0 is at (20, 15) and planes land there flying north
+        let plane = Plane {
+            target_altitude: 0,
+            destination: LocationId::Airport(1),
+            ..flying_plane('a', Point::new(20, 16), Direction::North, 1)
+        };
+        let mut game = game_with_planes(vec![plane]);
+        assert!(matches!(
+            game.tick(),
+            Err(LoseCondition::PlaneLandedAtWrongAirport {
+                plane: 'a',
+                airport: LocationId::Airport(0),
+                destination: LocationId::Airport(1),
+            })
+        ));
+    }
+
+    #[test]
+    fn exiting_at_the_wrong_exit_loses() {
+        // Exit 2 is at (29, 7) on the right border
+        let plane = flying_plane('a', Point::new(28, 7), Direction::East, Exit::EXIT_ALTITUDE);
+        let mut game = game_with_planes(vec![plane]);
+        assert!(matches!(
+            game.tick(),
+            Err(LoseCondition::PlaneExitedAtWrongExit {
+                plane: 'a',
+                exit: LocationId::Exit(2),
+                destination: LocationId::Exit(0),
+            })
+        ));
+    }
+
+    #[test]
+    fn exiting_at_the_wrong_altitude_loses() {
+        let plane = Plane {
+            destination: LocationId::Exit(2),
+            ..flying_plane('a', Point::new(28, 7), Direction::East, 7)
+        };
+        let mut game = game_with_planes(vec![plane]);
+        assert!(matches!(
+            game.tick(),
+            Err(LoseCondition::PlaneExitedAtWrongAltitude {
+                plane: 'a',
+                exit: LocationId::Exit(2),
+                altitude: 7,
+            })
+        ));
+    }
+
+    #[test]
+    fn exiting_at_the_destination_is_safe() {
+        let plane = Plane {
+            destination: LocationId::Exit(2),
+            ..flying_plane('a', Point::new(28, 7), Direction::East, Exit::EXIT_ALTITUDE)
+        };
+        let mut game = game_with_planes(vec![plane]);
+        assert!(game.tick().is_ok());
+        assert!(game.planes.is_empty());
+        assert_eq!(game.planes_safe, 1);
+    }
+
+    #[test]
+    fn reports_events() {
+        // Plane a is 7 squares west of beacon 0, and exit 2 is 17 squares further east
+        let plane = Plane {
+            destination: LocationId::Exit(2),
+            ..flying_plane('a', Point::new(5, 7), Direction::East, 7)
+        };
+        let mut game = game_with_planes(vec![plane]);
+        game.apply_command("aa9@b0").unwrap();
+        let mut events = vec![];
+        for _ in 0..23 {
+            events.extend(game.tick().ok().unwrap());
         }
+        assert!(matches!(
+            events.as_slice(),
+            [GameEvent::CommandTriggered { plane: 'a', .. }]
+        ));
+        assert!(matches!(
+            game.tick().ok().unwrap().as_slice(),
+            [GameEvent::PlaneExited {
+                plane: 'a',
+                exit: LocationId::Exit(2)
+            }]
+        ));
+    }
+
+    #[test]
+    fn predicts_conflicts_between_converging_planes() {
+        // Plane a and plane b fly towards each other and meet on the third tick
+        let planes = vec![
+            flying_plane('b', Point::new(11, 10), Direction::West, 7),
+            flying_plane('a', Point::new(5, 10), Direction::East, 7),
+        ];
+        let mut game = game_with_planes(planes);
+        let conflicts = game.predict_conflicts(10);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            (
+                conflicts[0].plane_a,
+                conflicts[0].plane_b,
+                conflicts[0].ticks
+            ),
+            ('a', 'b', 3)
+        );
+
+        // The prediction matches what actually happens
+        game.tick().ok().unwrap();
+        game.tick().ok().unwrap();
+        assert!(matches!(
+            game.tick().err().unwrap(),
+            LoseCondition::PlaneCollision { .. }
+        ));
+    }
+
+    #[test]
+    fn planes_at_different_altitudes_are_not_conflicts() {
+        let planes = vec![
+            flying_plane('a', Point::new(5, 10), Direction::East, 7),
+            flying_plane('b', Point::new(11, 10), Direction::West, 5),
+        ];
+        let game = game_with_planes(planes);
+        assert!(game.predict_conflicts(10).is_empty());
+    }
+
+    #[test]
+    fn projected_paths_list_each_position_once() {
+        // Propeller planes only move every other tick
+        let plane = Plane {
+            plane_type: PlaneType::Propeller,
+            ..flying_plane('a', Point::new(5, 10), Direction::East, 7)
+        };
+        assert_eq!(
+            plane.project_path(&default::create(), 4),
+            vec![Point::new(6, 10), Point::new(7, 10)]
+        );
     }
 }
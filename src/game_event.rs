@@ -0,0 +1,39 @@
+// Things that happen during a tick, returned by Game::tick so that the front end (or a bot) can react to them
+
+use std::fmt;
+
+use crate::command::Command;
+use crate::locations::LocationId;
+
+#[derive(Clone)]
+pub enum GameEvent {
+    PlaneSpawned { plane: char, location: LocationId },
+    PlaneLanded { plane: char, airport: LocationId },
+    PlaneExited { plane: char, exit: LocationId },
+    // A delayed command's trigger was reached, so it has started to run
+    CommandTriggered { plane: char, command: Command },
+    // The plane's fuel has just dropped to Game::LOW_FUEL_THRESHOLD
+    LowFuel { plane: char },
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameEvent::PlaneSpawned {
+                plane,
+                location: location @ LocationId::Airport(_),
+            } => write!(f, "Plane {plane} is waiting at {location}"),
+            GameEvent::PlaneSpawned { plane, location } => {
+                write!(f, "Plane {plane} entered at {location}")
+            }
+            GameEvent::PlaneLanded { plane, airport } => {
+                write!(f, "Plane {plane} landed at {airport}")
+            }
+            GameEvent::PlaneExited { plane, exit } => write!(f, "Plane {plane} exited at {exit}"),
+            GameEvent::CommandTriggered { plane, command } => {
+                write!(f, "Plane {plane} started {command}")
+            }
+            GameEvent::LowFuel { plane } => write!(f, "Plane {plane} is low on fuel"),
+        }
+    }
+}
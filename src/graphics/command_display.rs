@@ -1,11 +1,16 @@
-use pancurses::Window;
+use pancurses::{Window, A_REVERSE};
 
 use super::utils;
-use crate::game::Game;
+use super::DisplayState;
+use atc::game::Game;
 
-pub fn draw(window: &Window, _game: &Game, command_preview: &str) {
+pub fn draw(window: &Window, _game: &Game, display_state: &DisplayState) {
     window.erase();
     utils::draw_standard_border(window);
-    utils::wmvaddstr_multiline(window, 1, 1, command_preview);
+    utils::wmvaddstr_multiline(window, 1, 1, &display_state.input_preview);
+    if let Some(cursor) = display_state.input_cursor {
+        // (the real cursor is hidden, so highlight the char it is on instead)
+        window.mvchgat(1, 1 + cursor as i32, 1, A_REVERSE, 0);
+    }
     window.refresh();
 }
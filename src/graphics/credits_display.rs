@@ -1,9 +1,8 @@
 use pancurses::Window;
 
 use super::utils;
-use crate::game::Game;
 
-pub fn draw(window: &Window, _game: &Game) {
+pub fn draw(window: &Window) {
     window.erase();
     utils::draw_standard_border(window);
     window.mvaddstr(1, 1, "Implemented by ThatCoolCoder");
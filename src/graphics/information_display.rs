@@ -1,16 +1,18 @@
-use pancurses::Window;
+use pancurses::{Window, A_REVERSE};
 
 use super::utils;
-use crate::command::{Command, CommandTemporality, CommandType, DirectionalCommandValue};
-use crate::game::Game;
-use crate::locations::{Airport, Location};
-use crate::plane::{Plane, PlaneState, PlaneVisibility};
+use super::DisplayState;
+use atc::command::CommandType;
+use atc::game::{Conflict, Game};
+use atc::locations::LocationId;
+use atc::plane::{Plane, PlaneState, PlaneVisibility};
 
-pub fn draw(window: &Window, game: &Game) {
+pub fn draw(window: &Window, game: &Game, display_state: &DisplayState, conflicts: &[Conflict]) {
     window.erase();
     utils::draw_standard_border(window);
     draw_stats(window, game);
-    draw_plane_table(window, game);
+    let row = draw_plane_table(window, game, display_state.selected_plane);
+    draw_conflicts(window, conflicts, row + 1);
     window.refresh();
 }
 
@@ -22,10 +24,11 @@ fn draw_stats(window: &Window, game: &Game) {
     );
 }
 
-fn draw_plane_table(window: &Window, game: &Game) {
+fn draw_plane_table(window: &Window, game: &Game, selected_plane: Option<char>) -> i32 {
+    // Returns the row after the end of the table
     // Sort planes into groups
     let mut flying_planes = vec![];
-    let mut waiting_planes: Vec<(&Plane, &Airport)> = vec![];
+    let mut waiting_planes: Vec<(&Plane, LocationId)> = vec![];
     let mut sorted_planes = game.planes.iter().collect::<Vec<_>>();
     sorted_planes.sort_by_key(|p| p.name.to_ascii_lowercase());
     for plane in sorted_planes {
@@ -50,11 +53,13 @@ fn draw_plane_table(window: &Window, game: &Game) {
             1,
             format!(
                 "{plane}{fuel_char}{}  {}",
-                plane.destination.to_destination_string(),
+                plane.destination,
                 format_plane_directional_command(plane)
             ),
         );
+        highlight_if_selected(window, plane, selected_plane, row);
         row += 1;
+        draw_command_queue(window, plane, &mut row);
     }
 
     row += 1; // newline between sections
@@ -64,56 +69,81 @@ fn draw_plane_table(window: &Window, game: &Game) {
         window.mvaddstr(
             row,
             1,
-            format!(
-                "{plane} {}  Holding @ {}",
-                plane.destination.to_destination_string(),
-                airport.to_destination_string()
-            ),
+            format!("{plane} {}  Holding @ {}", plane.destination, airport),
         );
+        highlight_if_selected(window, plane, selected_plane, row);
         row += 1;
+        draw_command_queue(window, plane, &mut row);
+    }
+    row
+}
+
+fn draw_conflicts(window: &Window, conflicts: &[Conflict], mut row: i32) {
+    // List planes that will collide soon. They are already sorted soonest first
+    if conflicts.is_empty() {
+        return;
+    }
+    draw_rows_with_limit(window, &mut row, "", &["Conflicts".to_string()]);
+    let lines: Vec<_> = conflicts
+        .iter()
+        .map(|conflict| {
+            format!(
+                "{} & {} in {} tick{}",
+                conflict.plane_a,
+                conflict.plane_b,
+                conflict.ticks,
+                if conflict.ticks == 1 { "" } else { "s" }
+            )
+        })
+        .collect();
+    draw_rows_with_limit(window, &mut row, "", &lines);
+}
+
+fn highlight_if_selected(window: &Window, plane: &Plane, selected_plane: Option<char>, row: i32) {
+    if selected_plane == Some(plane.name) {
+        // (the border takes up the last two columns)
+        window.mvchgat(row, 1, window.get_max_x() - 3, A_REVERSE, 0);
     }
 }
 
 fn format_plane_directional_command(plane: &Plane) -> String {
     match plane.visibility {
-        PlaneVisibility::Marked => {
-            let command = plane.get_command(CommandType::Directional);
-            if let Some(cmd) = command {
-                if let Command::Directional(directional) = cmd {
-                    let value_format = match directional.value {
-                        DirectionalCommandValue::AbsoluteTurn(direction) => {
-                            format!("{}", direction.to_heading())
-                        }
-                        DirectionalCommandValue::Circle { to_right } => {
-                            let direction_char = if to_right { 'R' } else { 'L' };
-                            format!("Circ {direction_char}")
-                        }
-                        DirectionalCommandValue::HardTurn { to_right } => {
-                            let direction_char = if to_right { 'R' } else { 'L' };
-                            format!("Hard {direction_char}")
-                        }
-                        DirectionalCommandValue::SoftTurn { to_right } => {
-                            let direction_char = if to_right { 'R' } else { 'L' };
-                            format!("Soft {direction_char}")
-                        }
-                        DirectionalCommandValue::TurnTowards(location) => {
-                            format!("{}", location.to_destination_string())
-                        }
-                    };
-                    let temporality_format = match directional.temporality {
-                        CommandTemporality::Immediate => "".to_string(),
-                        CommandTemporality::Delayed(beacon) => {
-                            format!(" @ {}", beacon.to_destination_string())
-                        }
-                    };
-                    format!("{value_format}{temporality_format}")
-                } else {
-                    panic!("We asked for a directional command but did not get one");
-                }
-            } else {
-                "".to_string()
-            }
-        }
+        PlaneVisibility::Marked => match plane.get_command(CommandType::Directional) {
+            Some(command) => command.to_string(),
+            None => "".to_string(),
+        },
         _ => "----------".to_string(),
     }
 }
+
+fn draw_command_queue(window: &Window, plane: &Plane, row: &mut i32) {
+    // Show queued commands underneath the plane, in the order they will run
+    if plane.visibility != PlaneVisibility::Marked {
+        return;
+    }
+    let lines: Vec<_> = plane
+        .command_queue
+        .iter()
+        .map(|command| format!("& {command}"))
+        .collect();
+    draw_rows_with_limit(window, row, "     ", &lines);
+}
+
+fn draw_rows_with_limit(window: &Window, row: &mut i32, indent: &str, lines: &[String]) {
+    // Draw lines downwards from row, stopping above the bottom border.
+    // If they don't all fit then the last row says how many are missing
+    let last_row = window.get_max_y() - 2;
+    for (idx, line) in lines.iter().enumerate() {
+        let remaining = lines.len() - idx;
+        if *row > last_row {
+            return;
+        }
+        if *row == last_row && remaining > 1 {
+            window.mvaddstr(*row, 1, format!("{indent}+{remaining} more"));
+            *row += 1;
+            return;
+        }
+        window.mvaddstr(*row, 1, format!("{indent}{line}"));
+        *row += 1;
+    }
+}
@@ -0,0 +1,67 @@
+use pancurses::Window;
+
+use super::credits_display;
+use super::utils;
+use crate::message_log::MessageLog;
+
+pub fn draw(window: &Window, message_log: &MessageLog) {
+    // Nothing has happened yet at the start of a game, so show the credits until it has
+    if message_log.entries.is_empty() {
+        credits_display::draw(window);
+        return;
+    }
+
+    window.erase();
+    utils::draw_standard_border(window);
+    let (height, width) = window.get_max_yx();
+    let (text_height, text_width) = ((height - 2) as usize, (width - 3) as usize);
+
+    // Work backwards from the newest entry until the window is full
+    let mut lines = vec![];
+    for entry in message_log.visible_entries().iter().rev() {
+        let prefix = format!("{:>3} ", entry.tick);
+        let indent = " ".repeat(prefix.len());
+        let wrapped = wrap(&entry.text, text_width - prefix.len());
+        let entry_lines = wrapped.iter().enumerate().map(|(idx, line)| {
+            let start = if idx == 0 { &prefix } else { &indent };
+            format!("{start}{line}")
+        });
+        lines.splice(0..0, entry_lines);
+        if lines.len() >= text_height {
+            break;
+        }
+    }
+    let first_line = lines.len().saturating_sub(text_height);
+    for (row, line) in (1..).zip(&lines[first_line..]) {
+        window.mvaddstr(row, 1, line);
+    }
+
+    if message_log.scroll > 0 {
+        window.mvaddstr(height - 1, 2, format!(" {} newer ", message_log.scroll));
+    }
+    window.refresh();
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    // Split text into lines no longer than width, breaking between words where possible
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Words that are too long for a line on their own are broken up
+        while word.chars().count() > width {
+            let rest = word.split_off(word.char_indices().nth(width).unwrap().0);
+            lines.push(word);
+            word = rest;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    lines.push(line);
+    lines
+}
@@ -3,6 +3,7 @@
 mod command_display;
 mod credits_display;
 mod information_display;
+mod message_log_display;
 mod radar_display;
 mod utils;
 
@@ -11,14 +12,17 @@ use pancurses::{
     COLOR_GREEN, COLOR_RED, COLOR_WHITE, COLOR_YELLOW,
 };
 
-use crate::game::Game;
+use crate::message_log::MessageLog;
+use crate::trails::Trails;
+use atc::game::{Conflict, Game};
+use atc::point::Point;
 
 pub struct GraphicsContext {
     pub stdscr: Window,
     pub radar_window: Window,
     pub information_window: Window,
     pub command_window: Window,
-    pub credits_window: Window,
+    pub message_window: Window,
 }
 
 enum ColorPair {
@@ -29,6 +33,7 @@ enum ColorPair {
     MarkedPlane,
     IgnoredPlane,
     Markings,
+    Conflict,
 }
 
 const BOTTOM_ROW_HEIGHT: i32 = 5; // including border
@@ -65,7 +70,7 @@ pub fn initialize(game: &Game) -> GraphicsContext {
         height - BOTTOM_ROW_HEIGHT,
         middle_x - left_column_width,
     );
-    let credits_window = newwin(
+    let message_window = newwin(
         BOTTOM_ROW_HEIGHT,
         RIGHT_COLUMN_WIDTH,
         height - BOTTOM_ROW_HEIGHT,
@@ -76,15 +81,61 @@ pub fn initialize(game: &Game) -> GraphicsContext {
         radar_window,
         information_window,
         command_window,
-        credits_window,
+        message_window,
     }
 }
 
-pub fn draw(game: &Game, graphics_context: &GraphicsContext, input_preview: &str) {
-    radar_display::draw(&graphics_context.radar_window, game);
-    information_display::draw(&graphics_context.information_window, game);
-    command_display::draw(&graphics_context.command_window, game, input_preview);
-    credits_display::draw(&graphics_context.credits_window, game);
+pub struct DisplayState {
+    // Things shown on the screen that are part of the interface rather than the game
+    pub input_preview: String,
+    pub input_cursor: Option<usize>, // position of the cursor in the first line of input_preview, if it should be shown
+    pub selected_plane: Option<char>,
+    pub show_paths: bool, // of the selected plane, or every marked plane if none is selected
+    pub show_data_blocks: bool,
+}
+
+impl DisplayState {
+    pub fn from_text(text: &str) -> Self {
+        DisplayState {
+            input_preview: text.to_string(),
+            input_cursor: None,
+            selected_plane: None,
+            show_paths: false,
+            show_data_blocks: false,
+        }
+    }
+}
+
+pub fn draw(
+    game: &Game,
+    graphics_context: &GraphicsContext,
+    display_state: &DisplayState,
+    message_log: &MessageLog,
+    conflicts: &[Conflict],
+    trails: &Trails,
+) {
+    // conflicts should come from Game::predict_conflicts. It is slow, so it is only worked out when the game changes
+    radar_display::draw(
+        &graphics_context.radar_window,
+        game,
+        display_state,
+        conflicts,
+        trails,
+    );
+    information_display::draw(
+        &graphics_context.information_window,
+        game,
+        display_state,
+        conflicts,
+    );
+    command_display::draw(&graphics_context.command_window, game, display_state);
+    message_log_display::draw(&graphics_context.message_window, message_log);
+}
+
+pub fn radar_position(graphics_context: &GraphicsContext, screen_x: i32, screen_y: i32) -> Point {
+    // Convert a position on the screen (such as a mouse click) into a position on the radar
+    let (beg_y, beg_x) = graphics_context.radar_window.get_beg_yx();
+    Point::new((screen_x - beg_x) / 2, screen_y - beg_y)
 }
 
 fn init_colors() {
@@ -95,4 +146,5 @@ fn init_colors() {
     init_pair(ColorPair::MarkedPlane as i16, COLOR_WHITE, COLOR_BLACK);
     init_pair(ColorPair::IgnoredPlane as i16, COLOR_WHITE, COLOR_BLACK);
     init_pair(ColorPair::Markings as i16, COLOR_WHITE, COLOR_BLACK);
+    init_pair(ColorPair::Conflict as i16, COLOR_RED, COLOR_BLACK);
 }
@@ -1,24 +1,44 @@
 // Handles drawing radar, stats, planes list and credits
 
-use pancurses::{Window, A_BOLD};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use pancurses::{Window, A_BLINK, A_BOLD, A_DIM, A_REVERSE};
 
 use super::utils;
 use super::ColorPair;
-use crate::game::Game;
-use crate::plane::PlaneState;
-use crate::plane::PlaneVisibility;
-use crate::point::Point;
+use super::DisplayState;
+use crate::trails::Trails;
+use atc::game::{Conflict, Game};
+use atc::plane::{Plane, PlaneState, PlaneVisibility};
+use atc::point::Point;
+
+// How far ahead paths are shown. Propeller planes only move every other tick, so their paths are shorter
+const PATH_PROJECTION_TICKS: i32 = 20;
 
-pub fn draw(window: &Window, game: &Game) {
+pub fn draw(
+    window: &Window,
+    game: &Game,
+    display_state: &DisplayState,
+    conflicts: &[Conflict],
+    trails: &Trails,
+) {
     window.attroff(A_BOLD);
     draw_background_dots(window, game);
     draw_borders(window);
     window.attron(A_BOLD);
     draw_airways(window, game);
+    draw_trails(window, game, trails);
+    if display_state.show_paths {
+        draw_paths(window, game, display_state.selected_plane);
+    }
     draw_beacons(window, game);
     draw_exits(window, game);
     draw_airports(window, game);
-    draw_planes(window, game);
+    draw_planes(window, game, display_state.selected_plane, conflicts);
+    if display_state.show_data_blocks {
+        draw_data_blocks(window, game);
+    }
 
     window.refresh();
 }
@@ -39,7 +59,7 @@ fn draw_airways(window: &Window, game: &Game) {
     window.color_set(ColorPair::Airway as i16);
     for airway in &game.level.airways {
         let mut moved_len = 0;
-        let mut pos = airway.start.clone();
+        let mut pos = airway.start;
         let offset = airway.direction.to_point_offset();
         while moved_len < airway.length {
             let (x, y) = world_point_to_screen_position(&pos);
@@ -50,6 +70,43 @@ fn draw_airways(window: &Window, game: &Game) {
     }
 }
 
+fn draw_trails(window: &Window, game: &Game, trails: &Trails) {
+    // Show where planes have just been, so that their headings and recent turns can be seen at a glance.
+    // These are drawn before the locations, like paths
+    window.color_set(ColorPair::Markings as i16);
+    window.attroff(A_BOLD);
+    for plane in &game.planes {
+        if plane.visibility != PlaneVisibility::Marked {
+            window.attron(A_DIM);
+        }
+        for point in trails.get(plane.name) {
+            let (x, y) = world_point_to_screen_position(point);
+            window.mvaddstr(y, x, "•");
+        }
+        window.attroff(A_DIM);
+    }
+    window.attron(A_BOLD);
+}
+
+fn draw_paths(window: &Window, game: &Game, selected_plane: Option<char>) {
+    // Drawn before the locations so that the locations are drawn over them
+    window.color_set(ColorPair::Markings as i16);
+    let planes: Vec<&Plane> = match selected_plane {
+        Some(name) => game.get_plane_by_name(name).into_iter().collect(),
+        None => game
+            .planes
+            .iter()
+            .filter(|p| p.visibility == PlaneVisibility::Marked)
+            .collect(),
+    };
+    for plane in planes {
+        for point in plane.project_path(&game.level, PATH_PROJECTION_TICKS) {
+            let (x, y) = world_point_to_screen_position(&point);
+            window.mvaddch(y, x, '+');
+        }
+    }
+}
+
 fn draw_beacons(window: &Window, game: &Game) {
     window.color_set(ColorPair::Beacon as i16);
     for beacon in &game.level.beacons {
@@ -74,20 +131,99 @@ fn draw_airports(window: &Window, game: &Game) {
     }
 }
 
-fn draw_planes(window: &Window, game: &Game) {
-    window.color_set(ColorPair::MarkedPlane as i16);
+fn draw_planes(window: &Window, game: &Game, selected_plane: Option<char>, conflicts: &[Conflict]) {
     for plane in &game.planes {
         if let PlaneState::AtAirport(_) = plane.state {
             continue;
         }
+        // Planes that are going to collide soon stand out so that they can be sorted out in time
+        let is_conflicting = conflicts
+            .iter()
+            .any(|c| c.plane_a == plane.name || c.plane_b == plane.name);
+        if is_conflicting {
+            window.color_set(ColorPair::Conflict as i16);
+            window.attron(A_BLINK);
+        } else {
+            window.color_set(ColorPair::MarkedPlane as i16);
+        }
         if let PlaneVisibility::Marked = plane.visibility {
             window.attron(A_BOLD);
         } else {
             window.attroff(A_BOLD);
         }
+        if selected_plane == Some(plane.name) {
+            window.attron(A_REVERSE);
+        }
         let (x, y) = world_point_to_screen_position(&plane.position);
         window.mvaddstr(y, x, format!("{plane}"));
+        window.attroff(A_REVERSE | A_BLINK);
+    }
+}
+
+fn draw_data_blocks(window: &Window, game: &Game) {
+    // Show more about each marked plane next to it, in whichever nearby space covers the fewest other symbols
+    window.color_set(ColorPair::MarkedPlane as i16);
+    window.attroff(A_BOLD);
+
+    // Screen cells that have something in them. Every symbol on the radar is two characters wide
+    let mut occupied = HashSet::new();
+    let location_positions = (game.level.beacons.iter().map(|b| b.position))
+        .chain(game.level.exits.iter().map(|e| e.position))
+        .chain(game.level.airports.iter().map(|a| a.position));
+    let plane_positions = game
+        .planes
+        .iter()
+        .filter(|p| !p.is_at_airport())
+        .map(|p| p.position);
+    for position in location_positions.chain(plane_positions) {
+        let (x, y) = world_point_to_screen_position(&position);
+        occupied.insert((x, y));
+        occupied.insert((x + 1, y));
     }
+
+    let (height, width) = window.get_max_yx();
+    for plane in &game.planes {
+        if plane.is_at_airport() || plane.visibility != PlaneVisibility::Marked {
+            continue;
+        }
+        let text = format_data_block(plane, game);
+        let len = text.chars().count() as i32;
+        let (x, y) = world_point_to_screen_position(&plane.position);
+        // Places to try, in order of preference: right, left, above, below
+        let candidates = [(x + 3, y), (x - len - 1, y), (x, y - 1), (x, y + 1)];
+        let best = candidates
+            .iter()
+            .filter(|(cx, cy)| *cx >= 1 && cx + len <= width - 2 && *cy >= 1 && *cy < height - 1)
+            .min_by_key(|(cx, cy)| {
+                (*cx..cx + len)
+                    .filter(|cell_x| occupied.contains(&(*cell_x, *cy)))
+                    .count()
+            });
+        if let Some(&(block_x, block_y)) = best {
+            window.mvaddstr(block_y, block_x, &text);
+            occupied.extend((block_x..block_x + len).map(|cell_x| (cell_x, block_y)));
+        }
+    }
+    window.attron(A_BOLD);
+}
+
+fn format_data_block(plane: &Plane, game: &Game) -> String {
+    // Like "7↓2 A0*": altitude, then target altitude if it is changing, destination and a low fuel marker
+    let target = match plane.target_altitude.cmp(&plane.altitude) {
+        Ordering::Greater => format!("↑{}", plane.target_altitude),
+        Ordering::Less => format!("↓{}", plane.target_altitude),
+        Ordering::Equal => "".to_string(),
+    };
+    let destination = match game.level.get_location(plane.destination) {
+        Some(location) => location.to_destination_string(),
+        None => plane.destination.to_string(),
+    };
+    let fuel = if plane.remaining_fuel <= Game::LOW_FUEL_THRESHOLD {
+        "*"
+    } else {
+        ""
+    };
+    format!("{}{target} {destination}{fuel}", plane.altitude)
 }
 
 fn world_point_to_screen_position(point: &Point) -> (i32, i32) {
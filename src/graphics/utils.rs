@@ -5,6 +5,7 @@ pub fn draw_standard_border(window: &Window) {
     draw_border(window, '║', '║', '═', '═', '╔', '╗', '╚', '╝');
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_border(
     window: &Window,
     left: char,
@@ -50,9 +51,7 @@ pub fn vline_fix(window: &Window, y: i32, x: i32, char: char, len: i32) {
 pub fn wmvaddstr_multiline(window: &Window, y: i32, x: i32, string: &str) {
     // Like wmvaddstr but if there are multiple lines in the string it indents them equally.
     let lines = string.split('\n');
-    let mut current_row = y;
-    for line in lines {
+    for (current_row, line) in (y..).zip(lines) {
         window.mvaddstr(current_row, x, line);
-        current_row += 1;
     }
 }
@@ -1,33 +1,66 @@
-use crate::command_parser;
-use crate::game::{Game, LoseCondition};
-use crate::graphics::{self, GraphicsContext};
-use crate::levels::level::Level;
+use crate::graphics::{self, DisplayState, GraphicsContext};
+use crate::line_editor::LineEditor;
+use crate::message_log::MessageLog;
+use crate::trails::Trails;
+use atc::command_parser::{self, CommandPreview};
+use atc::game::{Conflict, Game, LoseCondition};
+use atc::game_event::GameEvent;
+use atc::levels::level::Level;
+use atc::replay::Recorder;
+use atc::save;
+use atc::scores::{self, Score};
 
-use pancurses::Input;
+use pancurses::{Input, BUTTON1_CLICKED, BUTTON1_PRESSED};
 
-pub struct InteractiveGame<'game> {
+pub struct InteractiveGame {
     // Extension of the basic game that provides support for drawing to the screen and getting input from keyboard
-    current_input_buffer: String,
+    input: LineEditor,
     current_input_error: String,
-    pub game: Game<'game>,
+    selected_plane: Option<char>, // commands are given to this plane without typing its name
+    show_paths: bool,
+    show_data_blocks: bool,
+    message_log: MessageLog,
+    conflicts: Vec<Conflict>, // updated whenever the planes or their commands change
+    trails: Trails,
+    pub game: Game,
+    level_name: String,
+    pub recorder: Option<Recorder>,
+    pub save_path: String,
+    pub player_name: String,
     graphics_context: GraphicsContext,
     fast_forward_next_frame: bool,
     frame_count: i32,
 }
 
-impl<'game> InteractiveGame<'game> {
+impl InteractiveGame {
     const FRAME_INTERVAL: i32 = 30;
+    pub const DEFAULT_SAVE_PATH: &'static str = "atc-save.json";
 
-    pub fn from_level(level: &'game mut Level) -> Self {
-        Self::from_game(Game::new(level))
+    pub fn from_level(level_name: &str, level: Level, seed: u64, trail_length: usize) -> Self {
+        Self::from_game(level_name, Game::new(level, seed), trail_length)
     }
 
-    pub fn from_game(game: Game<'game>) -> Self {
+    pub fn from_game(level_name: &str, game: Game, trail_length: usize) -> Self {
         let graphics_context = graphics::initialize(&game);
+        // Clicking on a plane selects it
+        pancurses::mousemask(BUTTON1_CLICKED | BUTTON1_PRESSED, None);
+        let conflicts = game.predict_conflicts(Game::CONFLICT_LOOKAHEAD_TICKS);
+        let mut trails = Trails::new(trail_length);
+        trails.update(&game, &[]);
         InteractiveGame {
-            current_input_buffer: "".to_string(),
+            input: LineEditor::new(),
             current_input_error: "".to_string(),
+            selected_plane: None,
+            show_paths: false,
+            show_data_blocks: false,
+            message_log: MessageLog::new(),
+            conflicts,
+            trails,
             game,
+            level_name: level_name.to_string(),
+            recorder: None,
+            save_path: Self::DEFAULT_SAVE_PATH.to_string(),
+            player_name: "anonymous".to_string(),
             graphics_context,
             fast_forward_next_frame: false,
             frame_count: 0,
@@ -38,29 +71,66 @@ impl<'game> InteractiveGame<'game> {
         let result: Result<(), LoseCondition>;
         let frames_per_tick = (self.game.level.move_interval * Self::FRAME_INTERVAL as f64) as i32;
         loop {
-            match self.graphics_context.stdscr.getch() {
-                Some(input) => match input {
+            if let Some(input) = self.graphics_context.stdscr.getch() {
+                match input {
                     Input::Character(c) => match c {
                         '\n' => self.buffer_to_command(),
                         // u+7f = backspace. In some terminal configs KeyBackspace isn't being created so we need to fix that
-                        '\u{7f}' => self.backspace(),
-                        other => self.current_input_buffer.push(other),
+                        '\u{7f}' => self.input.backspace(),
+                        '\t' if self.input.is_empty() => self.cycle_selection(true),
+                        '\t' => self.input.complete(&self.game, &self.command_prefix()),
+                        // Plane names are typed in lowercase, so uppercase is free for selecting planes
+                        c if c.is_ascii_uppercase() && self.input.is_empty() => {
+                            self.select_plane(c.to_ascii_lowercase())
+                        }
+                        '\u{1b}' => self.selected_plane = None, // escape
+                        '\u{15}' => self.input.clear_to_start(), // ctrl-u
+                        '\u{17}' => self.input.delete_word(),   // ctrl-w
+                        other => self.input.insert(other),
                     },
                     Input::KeyBackspace => {
-                        self.backspace();
+                        self.input.backspace();
                     }
+                    Input::KeyDC => self.input.delete(),
+                    Input::KeyLeft => self.input.move_left(),
+                    Input::KeyRight => self.input.move_right(),
+                    Input::KeyHome => self.input.move_to_start(),
+                    Input::KeyEnd => self.input.move_to_end(),
+                    Input::KeyUp => self.input.history_previous(),
+                    Input::KeyDown => self.input.history_next(),
+                    Input::KeyBTab if self.input.is_empty() => self.cycle_selection(false),
+                    Input::KeyMouse => self.select_clicked_plane(),
+                    Input::KeyPPage => self.message_log.scroll_up(),
+                    Input::KeyNPage => self.message_log.scroll_down(),
+                    Input::KeyF2 => self.save(),
+                    Input::KeyF4 => self.show_paths = !self.show_paths,
+                    Input::KeyF5 => self.show_data_blocks = !self.show_data_blocks,
                     _ => (),
-                },
-                None => (),
+                }
             }
 
-            let input_preview = if self.current_input_buffer == "" {
-                &self.current_input_error
+            let (input_preview, input_cursor) = if self.input.is_empty() {
+                (self.current_input_error.clone(), None)
             } else {
-                &self.current_input_buffer
+                let prompt_len = self.prompt().chars().count();
+                (self.preview_buffer(), Some(prompt_len + self.input.cursor))
+            };
+            let display_state = DisplayState {
+                input_preview,
+                input_cursor,
+                selected_plane: self.selected_plane,
+                show_paths: self.show_paths,
+                show_data_blocks: self.show_data_blocks,
             };
 
-            graphics::draw(&self.game, &self.graphics_context, input_preview);
+            graphics::draw(
+                &self.game,
+                &self.graphics_context,
+                &display_state,
+                &self.message_log,
+                &self.conflicts,
+                &self.trails,
+            );
 
             if !self.fast_forward_next_frame {
                 std::thread::sleep(std::time::Duration::from_millis(
@@ -72,8 +142,15 @@ impl<'game> InteractiveGame<'game> {
 
             if self.frame_count % frames_per_tick == 0 {
                 match self.game.tick() {
-                    Ok(()) => (),
+                    Ok(events) => {
+                        self.handle_events(&events);
+                        self.update_conflicts();
+                        self.trails.update(&self.game, &events);
+                    }
                     Err(e) => {
+                        if let Some(recorder) = &mut self.recorder {
+                            recorder.record_end(self.game.ticks);
+                        }
                         result = Err(e);
                         break;
                     }
@@ -83,18 +160,25 @@ impl<'game> InteractiveGame<'game> {
 
         let result_text = match result {
             Ok(_) => "somehow we got an ok result here, how?".to_string(),
-            Err(e) => e.to_string(),
-        } + ".\nPress space to exit";
-        graphics::draw(&self.game, &self.graphics_context, &result_text);
+            Err(e) => format!(
+                "{e}.\nSeed: {}    {}",
+                self.game.seed,
+                self.record_score(&e)
+            ),
+        } + "\nPress space to exit";
+        graphics::draw(
+            &self.game,
+            &self.graphics_context,
+            &DisplayState::from_text(&result_text),
+            &self.message_log,
+            &self.conflicts,
+            &self.trails,
+        );
 
         // Wait until space pressed
         loop {
-            if let Some(input) = self.graphics_context.stdscr.getch() {
-                if let Input::Character(c) = input {
-                    if c == ' ' {
-                        break;
-                    }
-                }
+            if let Some(Input::Character(' ')) = self.graphics_context.stdscr.getch() {
+                break;
             }
             std::thread::sleep(std::time::Duration::from_millis(
                 Self::FRAME_INTERVAL as u64,
@@ -103,30 +187,161 @@ impl<'game> InteractiveGame<'game> {
     }
 
     fn buffer_to_command(&mut self) {
-        let buffer = self.current_input_buffer.clone();
-        self.current_input_buffer = "".to_string();
+        let buffer = self.input.submit();
 
-        if buffer == "" {
+        if buffer.is_empty() {
             self.frame_count = -1; // todo: make a better way of resetting the frame counter
             self.fast_forward_next_frame = true;
             self.current_input_error = "".to_string();
             return;
         }
 
-        match command_parser::parse_command(&buffer, &self.game) {
-            Ok((command, plane_name)) => {
+        let command = self.command_prefix() + &buffer;
+        match self.game.apply_command(&command) {
+            Ok(message) => {
+                self.current_input_error = message.unwrap_or_default();
+                self.update_conflicts();
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.record_command(self.game.ticks, &command);
+                }
+            }
+            Err(error) => {
+                self.message_log.add(
+                    self.game.ticks,
+                    format!("Rejected {command}: {}", error.replace('\n', "; ")),
+                );
+                self.current_input_error = error;
+            }
+        }
+    }
+
+    fn preview_buffer(&self) -> String {
+        // Show what the command being typed will do, or what is wrong with it, before it is entered
+        let line = self.prompt() + &self.input.text;
+        if !self.input.completions.is_empty() {
+            return format!("{line}\nCompletions: {}", self.input.completions.join(" "));
+        }
+        let command = self.command_prefix() + &self.input.text;
+        let explanation = match command_parser::preview_command(&command, &self.game) {
+            CommandPreview::Incomplete => "".to_string(),
+            CommandPreview::Invalid(error) => format!("Invalid: {error}"),
+            CommandPreview::Valid(explanation) => explanation,
+        };
+        format!("{line}\n{explanation}")
+    }
+
+    fn command_prefix(&self) -> String {
+        // Commands typed while a plane is selected are for that plane, so its name goes in front of them
+        self.selected_plane.map(String::from).unwrap_or_default()
+    }
+
+    fn prompt(&self) -> String {
+        match self.selected_plane {
+            Some(plane_name) => format!("{plane_name}> "),
+            None => "".to_string(),
+        }
+    }
+
+    fn select_plane(&mut self, plane_name: char) {
+        match self.game.get_plane_by_name(plane_name) {
+            Some(_) => {
+                self.selected_plane = Some(plane_name);
                 self.current_input_error = "".to_string();
-                let plane = self.game.get_plane_by_name_mut(plane_name);
-                match plane {
-                    Some(p) => p.add_command(command),
-                    None => self.current_input_error = format!("Plane {plane_name} does not exist"),
+            }
+            None => self.current_input_error = format!("Plane {plane_name} does not exist"),
+        }
+    }
+
+    fn cycle_selection(&mut self, forwards: bool) {
+        // Select the next (or previous) flying plane, in the same order as the plane list
+        let mut names: Vec<_> = self
+            .game
+            .planes
+            .iter()
+            .filter(|p| !p.is_at_airport())
+            .map(|p| p.name)
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        names.sort();
+        let len = names.len();
+        let current_idx = self
+            .selected_plane
+            .and_then(|selected| names.iter().position(|&n| n == selected));
+        let idx = match current_idx {
+            Some(idx) if forwards => (idx + 1) % len,
+            Some(idx) => (idx + len - 1) % len,
+            None if forwards => 0,
+            None => len - 1,
+        };
+        self.selected_plane = Some(names[idx]);
+    }
+
+    fn select_clicked_plane(&mut self) {
+        let event = match pancurses::getmouse() {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+        if event.bstate & (BUTTON1_CLICKED | BUTTON1_PRESSED) == 0 {
+            return;
+        }
+        let position = graphics::radar_position(&self.graphics_context, event.x, event.y);
+        let clicked_plane = self
+            .game
+            .planes
+            .iter()
+            .find(|p| !p.is_at_airport() && p.position == position);
+        if let Some(plane) = clicked_plane {
+            self.selected_plane = Some(plane.name);
+        }
+    }
+
+    fn update_conflicts(&mut self) {
+        self.conflicts = self.game.predict_conflicts(Game::CONFLICT_LOOKAHEAD_TICKS);
+    }
+
+    fn handle_events(&mut self, events: &[GameEvent]) {
+        self.message_log.add_events(self.game.ticks, events);
+        for event in events {
+            match event {
+                // The selected plane has gone, so there is nothing to give commands to
+                GameEvent::PlaneLanded { plane, .. } | GameEvent::PlaneExited { plane, .. }
+                    if self.selected_plane == Some(*plane) =>
+                {
+                    self.selected_plane = None
                 }
+                _ => (),
             }
-            Err(error) => self.current_input_error = error,
         }
     }
 
-    fn backspace(&mut self) {
-        self.current_input_buffer.pop();
+    fn record_score(&self, lose_condition: &LoseCondition) -> String {
+        // Add the game to the high score table and return a message describing how well it went
+        let score = Score::from_game(
+            &self.game,
+            &self.level_name,
+            &self.player_name,
+            lose_condition,
+        );
+        let path = scores::default_scores_path();
+        let result =
+            scores::add_score(&path, score.clone()).and_then(|_| scores::load_scores(&path));
+        match result {
+            Ok(all_scores) => format!(
+                "Rank: {} of {}",
+                scores::rank_of(&all_scores, &score),
+                scores::ranked_scores(&all_scores, &self.level_name).len()
+            ),
+            Err(e) => e,
+        }
+    }
+
+    fn save(&mut self) {
+        self.current_input_error =
+            match save::save_game(&self.save_path, &self.level_name, &self.game) {
+                Ok(()) => format!("Game saved to {}", self.save_path),
+                Err(e) => e,
+            };
     }
 }
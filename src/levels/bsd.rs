@@ -0,0 +1,313 @@
+// Importing of scenario files from the original BSD atc, such as `default`, `easy` or `killer`.
+// They look like this:
+//
+//     update = 5;
+//     newplane = 5;
+//     width = 30;
+//     height = 21;
+//     exit:    ( 12  0 x ) ( 29  0 z ) ;
+//     beacon:  ( 12  7 ) ( 12 17 ) ;
+//     airport: ( 20 15 w ) ( 20 18 d ) ;
+//     line:    [ (  1  1 ) (  6  6 ) ] [ ( 12  1 ) ( 12  6 ) ] ;
+//
+// The original uses the same coordinates as we do, and its directions are the same keys that are used for commands.
+// Exits and airports are numbered in the order they appear in the file, which is what the original does too.
+
+use std::path::Path;
+
+use super::level::Level;
+use crate::direction::Direction;
+use crate::locations::*;
+use crate::point::Point;
+
+pub const BSD_LEVEL_FILE_EXTENSION: &str = "games";
+
+pub fn load_bsd_level_file(path: &Path) -> Result<Level, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read scenario file {}: {e}", path.display()))?;
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+    parse_bsd_level(&text, name)
+        .map_err(|e| format!("Invalid scenario file {}: {e}", path.display()))
+}
+
+pub fn parse_bsd_level(text: &str, name: &str) -> Result<Level, String> {
+    let tokens = tokenize(text);
+    let mut parser = Parser { tokens, idx: 0 };
+
+    let mut update = None;
+    let mut newplane = None;
+    let mut width = None;
+    let mut height = None;
+    let mut level = Level {
+        description: format!("Imported from the BSD atc scenario \"{name}\"."),
+        plane_spawn_chance: 0.,
+        move_interval: 0.,
+        size: Point::zero(),
+        airports: vec![],
+        beacons: vec![],
+        exits: vec![],
+        airways: vec![],
+    };
+
+    while let Some(keyword) = parser.next() {
+        match keyword.as_str() {
+            "update" | "newplane" | "width" | "height" => {
+                parser.expect("=")?;
+                let value = parser.number()?;
+                parser.expect(";")?;
+                match keyword.as_str() {
+                    "update" => update = Some(value),
+                    "newplane" => newplane = Some(value),
+                    "width" => width = Some(value),
+                    _ => height = Some(value),
+                }
+            }
+            "exit" | "beacon" | "airport" | "line" => {
+                parser.expect(":")?;
+                while !parser.accept(";") {
+                    match keyword.as_str() {
+                        "exit" => {
+                            let (position, direction) = parser.point_with_direction()?;
+                            level.exits.push(Exit {
+                                position,
+                                number: level.exits.len() as i32,
+                                entry_direction: direction,
+                            });
+                        }
+                        "beacon" => {
+                            let position = parser.point()?;
+                            level.beacons.push(Beacon {
+                                position,
+                                number: level.beacons.len() as i32,
+                            });
+                        }
+                        "airport" => {
+                            let (position, direction) = parser.point_with_direction()?;
+                            level.airports.push(Airport {
+                                position,
+                                number: level.airports.len() as i32,
+                                flight_direction: direction,
+                            });
+                        }
+                        _ => {
+                            parser.expect("[")?;
+                            let start = parser.point()?;
+                            let end = parser.point()?;
+                            parser.expect("]")?;
+                            level.airways.push(line_to_airway(start, end)?);
+                        }
+                    }
+                }
+            }
+            other => Err(format!("Unexpected \"{other}\""))?,
+        }
+    }
+
+    let update = update.ok_or("Scenario has no update")?;
+    let newplane = newplane.ok_or("Scenario has no newplane")?;
+    if update <= 0 || newplane <= 0 {
+        Err("update and newplane must be greater than 0")?;
+    }
+    level.move_interval = update as f64;
+    // The original spawns a plane when rand() % newplane == 0
+    level.plane_spawn_chance = 1. / newplane as f64;
+    level.size = Point::new(
+        width.ok_or("Scenario has no width")?,
+        height.ok_or("Scenario has no height")?,
+    );
+    Ok(level)
+}
+
+fn line_to_airway(start: Point, end: Point) -> Result<Airway, String> {
+    // Lines in the original are defined by their two (inclusive) ends
+    let delta = end.sub(&start);
+    if delta.x != 0 && delta.y != 0 && delta.x.abs() != delta.y.abs() {
+        Err(format!(
+            "Line from ({}, {}) to ({}, {}) is not horizontal, vertical or diagonal",
+            start.x, start.y, end.x, end.y
+        ))?;
+    }
+    let offset = Point::new(delta.x.signum(), delta.y.signum());
+    // (a line that is a single point can point in any direction)
+    let direction = (0..8)
+        .filter_map(|i| Direction::from_heading(i * 45))
+        .find(|d| d.to_point_offset().equals(&offset))
+        .unwrap_or(Direction::East);
+    Ok(Airway {
+        start,
+        length: delta.x.abs().max(delta.y.abs()) + 1,
+        direction,
+    })
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    for line in text.lines() {
+        // (everything after a # is a comment)
+        let line = line.split('#').next().unwrap_or("");
+        let mut current = String::new();
+        for c in line.chars() {
+            if c.is_whitespace() || "()[];:=".contains(c) {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                if !c.is_whitespace() {
+                    tokens.push(c.to_string());
+                }
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    idx: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.idx).cloned();
+        self.idx += 1;
+        token
+    }
+
+    fn accept(&mut self, expected: &str) -> bool {
+        // Consume the next token if it is the expected one
+        let found = self.tokens.get(self.idx).map(|t| t == expected) == Some(true);
+        if found {
+            self.idx += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected \"{expected}\" but found \"{token}\"")),
+            None => Err(format!("Expected \"{expected}\" but the file ended")),
+        }
+    }
+
+    fn number(&mut self) -> Result<i32, String> {
+        let token = self.next().ok_or("Expected a number but the file ended")?;
+        token
+            .parse()
+            .map_err(|_| format!("Expected a number but found \"{token}\""))
+    }
+
+    fn direction(&mut self) -> Result<Direction, String> {
+        let token = self
+            .next()
+            .ok_or("Expected a direction but the file ended")?;
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::from_keyboard_char(c),
+            _ => None,
+        }
+        .ok_or(format!("Expected a direction but found \"{token}\""))
+    }
+
+    fn point(&mut self) -> Result<Point, String> {
+        self.expect("(")?;
+        let x = self.number()?;
+        let y = self.number()?;
+        self.expect(")")?;
+        Ok(Point::new(x, y))
+    }
+
+    fn point_with_direction(&mut self) -> Result<(Point, Direction), String> {
+        self.expect("(")?;
+        let x = self.number()?;
+        let y = self.number()?;
+        let direction = self.direction()?;
+        self.expect(")")?;
+        Ok((Point::new(x, y), direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::default;
+
+    // The `default` scenario as shipped with the original
+    const DEFAULT_SCENARIO: &str = "
+        # This is the default scenario
+        update = 5;
+        newplane = 5;
+        width = 30;
+        height = 21;
+        exit:    ( 12  0 x ) ( 29  0 z ) ( 29  7 a ) ( 29 17 a )
+                 (  9 20 e ) (  0 13 d ) (  0  7 d ) (  0  0 c ) ;
+        beacon:  ( 12  7 ) ( 12 17 ) ;
+        airport: ( 20 15 w ) ( 20 18 d ) ;
+        line:    [ (  1  1 ) (  6  6 ) ]
+                 [ ( 12  1 ) ( 12  6 ) ]
+                 [ ( 13  7 ) ( 28  7 ) ]
+                 [ ( 28  1 ) ( 13 16 ) ]
+                 [ (  1 13 ) ( 11 13 ) ]
+                 [ ( 12  8 ) ( 12 16 ) ]
+                 [ ( 11 18 ) ( 10 19 ) ]
+                 [ ( 13 17 ) ( 28 17 ) ]
+                 [ (  1  7 ) ( 11  7 ) ] ;
+    ";
+
+    #[test]
+    fn converts_default_scenario() {
+        let level = parse_bsd_level(DEFAULT_SCENARIO, "default").unwrap();
+        // Our built in Default level was copied from this scenario, so its locations should be the same
+        let expected = default::create();
+        assert_eq!(level.size, expected.size);
+        assert_eq!(level.move_interval, 5.);
+        assert_eq!(level.plane_spawn_chance, 0.2);
+
+        assert_eq!(level.exits.len(), expected.exits.len());
+        for (exit, expected) in level.exits.iter().zip(&expected.exits) {
+            assert_eq!(exit.number, expected.number);
+            assert_eq!(exit.position, expected.position);
+            assert_eq!(exit.entry_direction, expected.entry_direction);
+        }
+        assert_eq!(level.airports.len(), expected.airports.len());
+        for (airport, expected) in level.airports.iter().zip(&expected.airports) {
+            assert_eq!(airport.number, expected.number);
+            assert_eq!(airport.position, expected.position);
+            assert_eq!(airport.flight_direction, expected.flight_direction);
+        }
+        let beacon_positions: Vec<_> = level.beacons.iter().map(|b| b.position).collect();
+        assert_eq!(
+            beacon_positions,
+            vec![Point::new(12, 7), Point::new(12, 17)]
+        );
+
+        assert_eq!(level.airways.len(), 9);
+        let diagonal = &level.airways[3];
+        assert_eq!(diagonal.start, Point::new(28, 1));
+        assert_eq!(diagonal.direction, Direction::SouthWest);
+        assert_eq!(diagonal.length, 16);
+    }
+
+    #[test]
+    fn rejects_crooked_lines() {
+        let text = "update = 5; newplane = 5; width = 30; height = 21; line: [ ( 1 1 ) ( 3 2 ) ] ;";
+        assert!(parse_bsd_level(text, "crooked").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_settings() {
+        let text = "update = 5; width = 30; height = 21;";
+        assert_eq!(
+            parse_bsd_level(text, "missing").err(),
+            Some("Scenario has no newplane".to_string())
+        );
+    }
+}
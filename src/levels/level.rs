@@ -1,6 +1,9 @@
 use crate::locations::*;
 use crate::point::Point;
 
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Level {
     pub description: String,
     pub plane_spawn_chance: f64, // chance of a new plane spawning on any given frame
@@ -11,3 +14,25 @@ pub struct Level {
     pub exits: Vec<Exit>,
     pub airways: Vec<Airway>,
 }
+
+impl Level {
+    pub fn get_location(&self, id: LocationId) -> Option<&dyn Location> {
+        match id {
+            LocationId::Airport(number) => self.get_airport(number).map(|a| a as &dyn Location),
+            LocationId::Beacon(number) => self.get_beacon(number).map(|b| b as &dyn Location),
+            LocationId::Exit(number) => self.get_exit(number).map(|e| e as &dyn Location),
+        }
+    }
+
+    pub fn get_airport(&self, number: i32) -> Option<&Airport> {
+        self.airports.iter().find(|a| a.number == number)
+    }
+
+    pub fn get_beacon(&self, number: i32) -> Option<&Beacon> {
+        self.beacons.iter().find(|b| b.number == number)
+    }
+
+    pub fn get_exit(&self, number: i32) -> Option<&Exit> {
+        self.exits.iter().find(|e| e.number == number)
+    }
+}
@@ -0,0 +1,166 @@
+// Loading of levels from TOML files, so that levels can be made without recompiling.
+// A level file contains every field of Level, and the name of the level is the name of the file without its extension.
+// Scenario files from the original BSD atc are loaded too if they have the extension bsd::BSD_LEVEL_FILE_EXTENSION.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::bsd;
+use super::level::Level;
+use super::LevelFactory;
+use crate::direction::Direction;
+use crate::point::Point;
+
+pub const LEVEL_FILE_EXTENSION: &str = "toml";
+
+pub fn search_path() -> Vec<PathBuf> {
+    // Directories that level files are loaded from. Later directories take priority
+    let mut directories = vec![PathBuf::from("levels")];
+    if let Some(config_dir) = dirs::config_dir() {
+        directories.push(config_dir.join("atc").join("levels"));
+    }
+    directories
+}
+
+pub fn load_level_file(path: &Path) -> Result<Level, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read level file {}: {e}", path.display()))?;
+    toml::from_str(&text).map_err(|e| format!("Invalid level file {}: {e}", path.display()))
+}
+
+pub fn level_to_toml(level: &Level) -> String {
+    // Written by hand rather than with toml::to_string, which puts every point in its own table.
+    // This way the output matches the format used in the readme
+    let point = |p: &Point| format!("{{ x = {}, y = {} }}", p.x, p.y);
+    let direction = |d: &Direction| toml::Value::String(format!("{d:?}"));
+
+    let mut text = format!(
+        "description = {}\nplane_spawn_chance = {}\nmove_interval = {}\nsize = {}\n",
+        toml::Value::String(level.description.clone()),
+        toml::Value::Float(level.plane_spawn_chance),
+        toml::Value::Float(level.move_interval),
+        point(&level.size)
+    );
+    for airport in &level.airports {
+        text += &format!(
+            "\n[[airports]]\nposition = {}\nflight_direction = {}\nnumber = {}\n",
+            point(&airport.position),
+            direction(&airport.flight_direction),
+            airport.number
+        );
+    }
+    for beacon in &level.beacons {
+        text += &format!(
+            "\n[[beacons]]\nposition = {}\nnumber = {}\n",
+            point(&beacon.position),
+            beacon.number
+        );
+    }
+    for exit in &level.exits {
+        text += &format!(
+            "\n[[exits]]\nposition = {}\nnumber = {}\nentry_direction = {}\n",
+            point(&exit.position),
+            exit.number,
+            direction(&exit.entry_direction)
+        );
+    }
+    for airway in &level.airways {
+        text += &format!(
+            "\n[[airways]]\nstart = {}\nlength = {}\ndirection = {}\n",
+            point(&airway.start),
+            airway.length,
+            direction(&airway.direction)
+        );
+    }
+    text
+}
+
+pub fn load_level_files(level_map: &mut HashMap<String, LevelFactory>) -> Vec<String> {
+    // Add the levels from every directory in the search path to level_map, replacing levels with the same name.
+    // Returns an error message for each file that could not be loaded
+    let mut errors = vec![];
+    for directory in search_path() {
+        // (a missing directory is fine, most people won't have made one)
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            let loaded = match path.extension().and_then(|e| e.to_str()) {
+                Some(LEVEL_FILE_EXTENSION) => load_level_file(&path),
+                Some(bsd::BSD_LEVEL_FILE_EXTENSION) => bsd::load_bsd_level_file(&path),
+                _ => continue,
+            };
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            match loaded {
+                Ok(level) => {
+                    level_map.insert(name, Box::new(move || level.clone()));
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::default;
+
+    fn temp_level_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "atc-test-{}-{name}.{LEVEL_FILE_EXTENSION}",
+            std::process::id()
+        ))
+    }
+
+    fn load_text(name: &str, text: &str) -> Result<Level, String> {
+        let path = temp_level_path(name);
+        fs::write(&path, text).unwrap();
+        let level = load_level_file(&path);
+        let _ = fs::remove_file(&path);
+        level
+    }
+
+    #[test]
+    fn loads_level_file() {
+        let level = default::create();
+        let loaded = load_text("default", &level_to_toml(&level)).unwrap();
+        assert_eq!(loaded.description, level.description);
+        assert_eq!(loaded.size, level.size);
+        assert_eq!(loaded.airports.len(), level.airports.len());
+        assert_eq!(loaded.beacons.len(), level.beacons.len());
+        assert_eq!(loaded.exits.len(), level.exits.len());
+        assert_eq!(loaded.airways.len(), level.airways.len());
+        assert_eq!(loaded.exits[2].position, level.exits[2].position);
+    }
+
+    #[test]
+    fn rejects_invalid_level_files() {
+        assert!(load_text("invalid", "description = ")
+            .err()
+            .unwrap()
+            .starts_with("Invalid level file"));
+        // Every field of Level is needed
+        assert!(load_text("missing", "description = \"No size\"")
+            .err()
+            .unwrap()
+            .starts_with("Invalid level file"));
+    }
+
+    #[test]
+    fn rejects_missing_level_files() {
+        let path = temp_level_path("does-not-exist");
+        assert!(load_level_file(&path)
+            .err()
+            .unwrap()
+            .starts_with("Could not read level file"));
+    }
+}
@@ -1,7 +1,25 @@
-// All files in this module except for level.rs are levels
+// All files in this module except for level.rs, loader.rs, bsd.rs and validator.rs are levels
+pub mod bsd;
 pub mod level;
+pub mod loader;
+pub mod validator;
 
 pub mod airportcross;
 pub mod default;
 pub mod seattle;
 pub mod small;
+
+use std::collections::HashMap;
+
+pub type LevelFactory = Box<dyn Fn() -> level::Level>;
+
+pub fn level_map() -> HashMap<String, LevelFactory> {
+    // Map of the built in levels. Levels from files are added to it with loader::load_level_files
+    let mut level_map: HashMap<String, LevelFactory> = HashMap::new();
+    // Please order the levels alphabetically
+    level_map.insert("Airportcross".to_string(), Box::new(airportcross::create));
+    level_map.insert("Default".to_string(), Box::new(default::create));
+    level_map.insert("Seattle".to_string(), Box::new(seattle::create));
+    level_map.insert("Small".to_string(), Box::new(small::create));
+    level_map
+}
@@ -0,0 +1,441 @@
+// Checks a level against the rules that levels need to follow, which are explained in the readme.
+// Breaking an error rule makes a level unplayable or broken, while warnings are just for conventions.
+
+use std::fmt;
+
+use super::level::Level;
+use crate::direction::Direction;
+use crate::point::Point;
+
+#[derive(PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+// Number of squares that a plane needs to be able to fly straight after taking off without reaching the border
+pub const MIN_TAKEOFF_DISTANCE: i32 = 2;
+
+pub fn validate_level(level: &Level) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    if level.size.x < 3 || level.size.y < 3 {
+        diagnostics.push(error(format!(
+            "Level size ({}, {}) is too small, it must be at least (3, 3)",
+            level.size.x, level.size.y
+        )));
+        return diagnostics;
+    }
+    if level.plane_spawn_chance <= 0. || level.plane_spawn_chance > 1. {
+        diagnostics.push(error(format!(
+            "plane_spawn_chance is {}, it must be more than 0 and at most 1",
+            level.plane_spawn_chance
+        )));
+    }
+    if level.move_interval <= 0. {
+        diagnostics.push(error(format!(
+            "move_interval is {}, it must be more than 0",
+            level.move_interval
+        )));
+    }
+    if level.exits.is_empty() && level.airports.is_empty() {
+        diagnostics.push(error(
+            "Level has no exits or airports, so planes can't spawn".to_string(),
+        ));
+    }
+
+    check_exits(level, &mut diagnostics);
+    check_airports(level, &mut diagnostics);
+    check_beacons(level, &mut diagnostics);
+    check_airways(level, &mut diagnostics);
+
+    diagnostics
+}
+
+fn check_exits(level: &Level, diagnostics: &mut Vec<Diagnostic>) {
+    for exit in &level.exits {
+        let name = format!("Exit {}", exit.number);
+        if !is_on_border(level, &exit.position) {
+            diagnostics.push(error(format!(
+                "{name} at {} is not on the border",
+                format_point(&exit.position)
+            )));
+        }
+        let first_position = exit.position.add(&exit.entry_direction.to_point_offset());
+        if !is_inside_border(level, &first_position) {
+            diagnostics.push(error(format!(
+                "{name} has entry direction {:?}, which does not point into the map",
+                exit.entry_direction
+            )));
+        }
+    }
+
+    let numbered: Vec<_> = level.exits.iter().map(|e| (e.number, e.position)).collect();
+    check_numbering("Exit", &numbered, diagnostics);
+    check_ordering(
+        "Exit",
+        "clockwise starting from the top left",
+        &numbered,
+        |p| clockwise_position(level, p),
+        diagnostics,
+    );
+}
+
+fn check_airports(level: &Level, diagnostics: &mut Vec<Diagnostic>) {
+    for airport in &level.airports {
+        let name = format!("Airport {}", airport.number);
+        if !is_inside_border(level, &airport.position) {
+            diagnostics.push(error(format!(
+                "{name} at {} is not inside the border",
+                format_point(&airport.position)
+            )));
+            continue;
+        }
+        if !has_room_to_take_off(level, &airport.position, airport.flight_direction) {
+            diagnostics.push(error(format!(
+                "{name} has flight direction {:?}, which doesn't leave room to take off before reaching the border",
+                airport.flight_direction
+            )));
+        }
+    }
+
+    let numbered: Vec<_> = level
+        .airports
+        .iter()
+        .map(|a| (a.number, a.position))
+        .collect();
+    check_numbering("Airport", &numbered, diagnostics);
+    check_ordering(
+        "Airport",
+        "from top to bottom",
+        &numbered,
+        |p| p.y,
+        diagnostics,
+    );
+}
+
+fn check_beacons(level: &Level, diagnostics: &mut Vec<Diagnostic>) {
+    for beacon in &level.beacons {
+        if !is_inside_border(level, &beacon.position) {
+            diagnostics.push(error(format!(
+                "Beacon {} at {} is not inside the border",
+                beacon.number,
+                format_point(&beacon.position)
+            )));
+        }
+    }
+
+    let numbered: Vec<_> = level
+        .beacons
+        .iter()
+        .map(|b| (b.number, b.position))
+        .collect();
+    check_numbering("Beacon", &numbered, diagnostics);
+    check_ordering(
+        "Beacon",
+        "from top to bottom",
+        &numbered,
+        |p| p.y,
+        diagnostics,
+    );
+}
+
+fn check_airways(level: &Level, diagnostics: &mut Vec<Diagnostic>) {
+    for (idx, airway) in level.airways.iter().enumerate() {
+        if airway.length < 1 {
+            diagnostics.push(error(format!(
+                "Airway {idx} has length {}, it must be at least 1",
+                airway.length
+            )));
+            continue;
+        }
+        let offset = airway.direction.to_point_offset();
+        let end = Point::new(
+            airway.start.x + offset.x * (airway.length - 1),
+            airway.start.y + offset.y * (airway.length - 1),
+        );
+        if !is_in_bounds(level, &airway.start) || !is_in_bounds(level, &end) {
+            diagnostics.push(error(format!(
+                "Airway {idx} from {} to {} goes out of bounds",
+                format_point(&airway.start),
+                format_point(&end)
+            )));
+        }
+    }
+}
+
+fn check_numbering(kind: &str, numbered: &[(i32, Point)], diagnostics: &mut Vec<Diagnostic>) {
+    // Numbers should be 0, 1, 2... with none missing or repeated
+    let mut numbers: Vec<_> = numbered.iter().map(|(number, _)| *number).collect();
+    numbers.sort();
+    for (expected, number) in numbers.iter().enumerate() {
+        if *number != expected as i32 {
+            diagnostics.push(error(format!(
+                "{kind}s are not numbered from 0 without gaps or repeats (numbers are {numbers:?})"
+            )));
+            return;
+        }
+    }
+}
+
+fn check_ordering<F: Fn(&Point) -> i32>(
+    kind: &str,
+    convention: &str,
+    numbered: &[(i32, Point)],
+    sort_key: F,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut sorted = numbered.to_vec();
+    sorted.sort_by_key(|(number, _)| *number);
+    for pair in sorted.windows(2) {
+        let ((number_a, position_a), (number_b, position_b)) = (pair[0], pair[1]);
+        if sort_key(&position_a) > sort_key(&position_b) {
+            diagnostics.push(warning(format!(
+                "{kind} {number_b} comes before {kind} {number_a}, but by convention they are numbered {convention}"
+            )));
+        }
+    }
+}
+
+fn has_room_to_take_off(level: &Level, position: &Point, direction: Direction) -> bool {
+    let offset = direction.to_point_offset();
+    let mut position = *position;
+    for _ in 0..MIN_TAKEOFF_DISTANCE {
+        position = position.add(&offset);
+        if !is_inside_border(level, &position) {
+            return false;
+        }
+    }
+    true
+}
+
+fn clockwise_position(level: &Level, point: &Point) -> i32 {
+    // How far around the border a point is, going clockwise from the top left.
+    // The top left corner itself is treated as being at the end, as it is the last exit in the default level
+    let (max_x, max_y) = (level.size.x - 1, level.size.y - 1);
+    if point.y == 0 && point.x > 0 {
+        point.x
+    } else if point.x == max_x {
+        max_x + point.y
+    } else if point.y == max_y {
+        max_x + max_y + (max_x - point.x)
+    } else {
+        max_x * 2 + max_y + (max_y - point.y)
+    }
+}
+
+fn is_in_bounds(level: &Level, point: &Point) -> bool {
+    point.x >= 0 && point.y >= 0 && point.x < level.size.x && point.y < level.size.y
+}
+
+fn is_on_border(level: &Level, point: &Point) -> bool {
+    is_in_bounds(level, point) && !is_inside_border(level, point)
+}
+
+fn is_inside_border(level: &Level, point: &Point) -> bool {
+    point.x > 0 && point.y > 0 && point.x < level.size.x - 1 && point.y < level.size.y - 1
+}
+
+fn format_point(point: &Point) -> String {
+    format!("({}, {})", point.x, point.y)
+}
+
+fn error(message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        message,
+    }
+}
+
+fn warning(message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::level_map;
+    use crate::locations::*;
+
+    fn small_level() -> Level {
+        // A 10x10 level with one exit and nothing else, which has no problems
+        Level {
+            description: "Test level".to_string(),
+            plane_spawn_chance: 0.1,
+            move_interval: 1.,
+            size: Point::new(10, 10),
+            airports: vec![],
+            beacons: vec![],
+            exits: vec![Exit {
+                position: Point::new(0, 5),
+                number: 0,
+                entry_direction: Direction::East,
+            }],
+            airways: vec![],
+        }
+    }
+
+    fn messages(level: &Level, severity: Severity) -> Vec<String> {
+        validate_level(level)
+            .into_iter()
+            .filter(|d| d.severity == severity)
+            .map(|d| d.message)
+            .collect()
+    }
+
+    fn errors(level: &Level) -> Vec<String> {
+        messages(level, Severity::Error)
+    }
+
+    #[test]
+    fn built_in_levels_have_no_errors() {
+        for (name, factory) in level_map() {
+            assert_eq!(errors(&factory()), Vec::<String>::new(), "{name}");
+        }
+        assert!(validate_level(&small_level()).is_empty());
+    }
+
+    #[test]
+    fn exits_must_be_on_the_border() {
+        let mut level = small_level();
+        level.exits[0].position = Point::new(5, 5);
+        assert_eq!(
+            errors(&level),
+            vec!["Exit 0 at (5, 5) is not on the border"]
+        );
+
+        // Just outside the map doesn't count as the border
+        level.exits[0].position = Point::new(10, 5);
+        assert!(errors(&level).contains(&"Exit 0 at (10, 5) is not on the border".to_string()));
+    }
+
+    #[test]
+    fn exits_must_point_into_the_map() {
+        let mut level = small_level();
+        level.exits[0].entry_direction = Direction::West;
+        assert_eq!(
+            errors(&level),
+            vec!["Exit 0 has entry direction West, which does not point into the map"]
+        );
+
+        // Pointing along the border doesn't reach the inside either
+        level.exits[0].entry_direction = Direction::North;
+        assert_eq!(errors(&level).len(), 1);
+        level.exits[0].entry_direction = Direction::NorthEast;
+        assert!(errors(&level).is_empty());
+    }
+
+    #[test]
+    fn airports_need_room_to_take_off() {
+        let mut level = small_level();
+        level.airports.push(Airport {
+            position: Point::new(MIN_TAKEOFF_DISTANCE + 1, 5),
+            flight_direction: Direction::West,
+            number: 0,
+        });
+        assert!(errors(&level).is_empty());
+
+        level.airports[0].position.x = MIN_TAKEOFF_DISTANCE;
+        assert_eq!(
+            errors(&level),
+            vec!["Airport 0 has flight direction West, which doesn't leave room to take off before reaching the border"]
+        );
+
+        level.airports[0].position.x = 0;
+        assert_eq!(
+            errors(&level),
+            vec!["Airport 0 at (0, 5) is not inside the border"]
+        );
+    }
+
+    #[test]
+    fn locations_must_be_numbered_from_zero() {
+        let mut level = small_level();
+        level.beacons = vec![
+            Beacon {
+                position: Point::new(5, 2),
+                number: 0,
+            },
+            Beacon {
+                position: Point::new(5, 4),
+                number: 2,
+            },
+        ];
+        assert_eq!(
+            errors(&level),
+            vec!["Beacons are not numbered from 0 without gaps or repeats (numbers are [0, 2])"]
+        );
+
+        level.beacons[1].number = 0;
+        assert_eq!(errors(&level).len(), 1);
+
+        level.beacons[1].number = 1;
+        assert!(validate_level(&level).is_empty());
+    }
+
+    #[test]
+    fn locations_out_of_order_are_warnings() {
+        let mut level = small_level();
+        level.beacons = vec![
+            Beacon {
+                position: Point::new(5, 6),
+                number: 0,
+            },
+            Beacon {
+                position: Point::new(5, 2),
+                number: 1,
+            },
+        ];
+        assert!(errors(&level).is_empty());
+        assert_eq!(
+            messages(&level, Severity::Warning),
+            vec!["Beacon 1 comes before Beacon 0, but by convention they are numbered from top to bottom"]
+        );
+
+        level.exits.push(Exit {
+            position: Point::new(5, 0),
+            number: 1,
+            entry_direction: Direction::South,
+        });
+        assert_eq!(messages(&level, Severity::Warning).len(), 2);
+    }
+
+    #[test]
+    fn airways_must_stay_in_bounds() {
+        let mut level = small_level();
+        level.airways.push(Airway {
+            start: Point::new(7, 5),
+            length: 3,
+            direction: Direction::East,
+        });
+        assert!(errors(&level).is_empty());
+
+        level.airways[0].length = 4;
+        assert_eq!(
+            errors(&level),
+            vec!["Airway 0 from (7, 5) to (10, 5) goes out of bounds"]
+        );
+
+        level.airways[0].length = 0;
+        assert_eq!(
+            errors(&level),
+            vec!["Airway 0 has length 0, it must be at least 1"]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+// The simulation side of the game, which can run without a terminal.
+// The pancurses front end in main.rs is built on top of this.
+
+pub mod command;
+pub mod command_parser;
+pub mod direction;
+pub mod game;
+pub mod game_event;
+pub mod levels;
+pub mod locations;
+pub mod plane;
+pub mod point;
+pub mod replay;
+pub mod save;
+pub mod scores;
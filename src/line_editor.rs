@@ -0,0 +1,302 @@
+use atc::command_parser;
+use atc::game::Game;
+
+pub struct LineEditor {
+    // The line of text that commands are typed into, with a cursor, history and tab completion
+    pub text: String,
+    pub cursor: usize,            // index of the char that the cursor is before
+    pub completions: Vec<String>, // shown after pressing tab when there is more than one way to complete the text
+    history: Vec<String>,
+    history_idx: Option<usize>, // which entry of history is being shown, if any
+    draft: String,              // what was being typed before moving through history
+}
+
+impl LineEditor {
+    const MAX_HISTORY_LEN: usize = 100;
+
+    pub fn new() -> Self {
+        LineEditor {
+            text: "".to_string(),
+            cursor: 0,
+            completions: vec![],
+            history: vec![],
+            history_idx: None,
+            draft: "".to_string(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn submit(&mut self) -> String {
+        // Take the text so it can be run as a command, and remember it for later
+        let text = std::mem::take(&mut self.text);
+        if !text.is_empty() && self.history.last() != Some(&text) {
+            self.history.push(text.clone());
+            if self.history.len() > Self::MAX_HISTORY_LEN {
+                self.history.remove(0);
+            }
+        }
+        self.history_idx = None;
+        self.set_text("".to_string());
+        text
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_idx(self.cursor);
+        self.text.insert(idx, c);
+        self.cursor += 1;
+        self.completions.clear();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let idx = self.byte_idx(self.cursor);
+            self.text.remove(idx);
+        }
+        self.completions.clear();
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let idx = self.byte_idx(self.cursor);
+            self.text.remove(idx);
+        }
+        self.completions.clear();
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    pub fn clear_to_start(&mut self) {
+        // Ctrl-U
+        let idx = self.byte_idx(self.cursor);
+        self.text.replace_range(..idx, "");
+        self.cursor = 0;
+        self.completions.clear();
+    }
+
+    pub fn delete_word(&mut self) {
+        // Ctrl-W. Deletes back to the previous space, along with any spaces before the cursor
+        let chars: Vec<_> = self.text.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1] == ' ' {
+            start -= 1;
+        }
+        while start > 0 && chars[start - 1] != ' ' {
+            start -= 1;
+        }
+        let (start_idx, end_idx) = (self.byte_idx(start), self.byte_idx(self.cursor));
+        self.text.replace_range(start_idx..end_idx, "");
+        self.cursor = start;
+        self.completions.clear();
+    }
+
+    pub fn history_previous(&mut self) {
+        let idx = match self.history_idx {
+            Some(0) => return,
+            Some(idx) => idx - 1,
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.text.clone();
+                self.history.len() - 1
+            }
+        };
+        self.history_idx = Some(idx);
+        self.set_text(self.history[idx].clone());
+    }
+
+    pub fn history_next(&mut self) {
+        match self.history_idx {
+            Some(idx) if idx + 1 < self.history.len() => {
+                self.history_idx = Some(idx + 1);
+                self.set_text(self.history[idx + 1].clone());
+            }
+            Some(_) => {
+                self.history_idx = None;
+                self.set_text(self.draft.clone());
+            }
+            None => (),
+        }
+    }
+
+    pub fn complete(&mut self, game: &Game, prefix: &str) {
+        // Add as much as can be worked out to the text before the cursor.
+        // If there are several possibilities then they are kept in completions so that they can be shown.
+        // prefix is text that will be put before the command when it is run, such as the selected plane's name
+        let before_cursor: String = self.text.chars().take(self.cursor).collect();
+        let completions = command_parser::completions(&format!("{prefix}{before_cursor}"), game);
+        let common_prefix = completions.iter().skip(1).fold(
+            completions.first().cloned().unwrap_or_default(),
+            |prefix, c| {
+                prefix
+                    .chars()
+                    .zip(c.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            },
+        );
+        for c in common_prefix.chars() {
+            self.insert(c);
+        }
+        if completions.len() > 1 {
+            self.completions = completions;
+        }
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.cursor = self.len();
+        self.completions.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_idx(&self, char_idx: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_idx)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.text.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atc::direction::Direction;
+    use atc::levels::default;
+    use atc::plane::PlaneState;
+    use atc::point::Point;
+
+    fn type_text(editor: &mut LineEditor, text: &str) {
+        for c in text.chars() {
+            editor.insert(c);
+        }
+    }
+
+    #[test]
+    fn edits_at_the_cursor() {
+        let mut editor = LineEditor::new();
+        type_text(&mut editor, "ac");
+        editor.move_left();
+        editor.insert('t');
+        assert_eq!(editor.text, "atc");
+        assert_eq!(editor.cursor, 2);
+
+        editor.move_to_start();
+        editor.move_left();
+        assert_eq!(editor.cursor, 0);
+        editor.delete();
+        assert_eq!(editor.text, "tc");
+
+        editor.move_to_end();
+        editor.move_right();
+        assert_eq!(editor.cursor, 2);
+        editor.backspace();
+        assert_eq!(editor.text, "t");
+        assert_eq!(editor.cursor, 1);
+    }
+
+    #[test]
+    fn deletes_words_and_lines() {
+        let mut editor = LineEditor::new();
+        type_text(&mut editor, "ab cd ef");
+        editor.delete_word();
+        assert_eq!(editor.text, "ab cd ");
+        // Spaces before the cursor go along with the word before them
+        editor.delete_word();
+        assert_eq!(editor.text, "ab ");
+
+        type_text(&mut editor, "gh");
+        editor.move_left();
+        editor.clear_to_start();
+        assert_eq!(editor.text, "h");
+        assert_eq!(editor.cursor, 0);
+    }
+
+    #[test]
+    fn keeps_limited_history() {
+        let mut editor = LineEditor::new();
+        for idx in 0..LineEditor::MAX_HISTORY_LEN + 5 {
+            type_text(&mut editor, &format!("command {idx}"));
+            assert_eq!(editor.submit(), format!("command {idx}"));
+        }
+        // Repeating the last command doesn't add it again
+        type_text(
+            &mut editor,
+            &format!("command {}", LineEditor::MAX_HISTORY_LEN + 4),
+        );
+        editor.submit();
+
+        type_text(&mut editor, "draft");
+        editor.history_previous();
+        assert_eq!(editor.text, "command 104");
+        editor.history_previous();
+        assert_eq!(editor.text, "command 103");
+        for _ in 0..200 {
+            editor.history_previous();
+        }
+        // The oldest commands were dropped
+        assert_eq!(editor.text, "command 5");
+
+        for _ in 0..LineEditor::MAX_HISTORY_LEN - 1 {
+            editor.history_next();
+        }
+        assert_eq!(editor.text, "command 104");
+        editor.history_next();
+        assert_eq!(editor.text, "draft");
+        assert_eq!(editor.cursor, 5);
+    }
+
+    #[test]
+    fn completes_common_prefix() {
+        // Plane a is flying south over beacons 0 and 1
+        let mut game = Game::new(default::create(), 0);
+        let plane = &mut game.planes[0];
+        plane.name = 'a';
+        plane.state = PlaneState::Flying;
+        plane.position = Point::new(12, 3);
+        plane.direction = Direction::South;
+
+        let mut editor = LineEditor::new();
+        type_text(&mut editor, "atw@");
+        editor.complete(&game, "");
+        assert_eq!(editor.text, "atw@b");
+        assert_eq!(editor.completions, vec!["b0", "b1"]);
+
+        // The selected plane's name goes before the text
+        let mut editor_with_prefix = LineEditor::new();
+        type_text(&mut editor_with_prefix, "tw@");
+        editor_with_prefix.complete(&game, "a");
+        assert_eq!(editor_with_prefix.text, "tw@b");
+
+        // Typing clears the completions
+        type_text(&mut editor, "1");
+        assert!(editor.completions.is_empty());
+
+        // A single completion is inserted completely
+        let mut editor = LineEditor::new();
+        editor.complete(&game, "");
+        assert_eq!(editor.text, "a");
+        assert!(editor.completions.is_empty());
+    }
+}
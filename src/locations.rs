@@ -4,20 +4,58 @@ use std::fmt;
 use crate::direction::Direction;
 use crate::point::Point;
 
+use serde::{Deserialize, Serialize};
+
+// Identifies a location within a level, so that it can be referred to without borrowing the level.
+// Resolve it into the actual location using Level::get_location
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum LocationId {
+    Airport(i32),
+    Beacon(i32),
+    Exit(i32),
+}
+
+// Uses the same format as Location::to_destination_string
+impl fmt::Display for LocationId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationId::Airport(number) => write!(f, "A{number}"),
+            LocationId::Beacon(number) => write!(f, "B{number}"),
+            LocationId::Exit(number) => write!(f, "E{number}"),
+        }
+    }
+}
+
+impl LocationId {
+    pub fn name(&self) -> String {
+        // Name for use in sentences, like "beacon 3"
+        match self {
+            LocationId::Airport(number) => format!("airport {number}"),
+            LocationId::Beacon(number) => format!("beacon {number}"),
+            LocationId::Exit(number) => format!("exit {number}"),
+        }
+    }
+}
+
 // Would be lovely to make a macro to derive this but making macros looks hard
 pub trait Location: fmt::Display {
+    fn id(&self) -> LocationId;
     fn get_position(&self) -> Point;
     fn accessible_from_direction(&self, direction: &Direction) -> bool;
     fn can_exit_at_alt(&self, alt: i32) -> bool;
     fn to_destination_string(&self) -> String;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Airport {
     pub position: Point,
     pub flight_direction: Direction,
     pub number: i32,
 }
 impl Location for Airport {
+    fn id(&self) -> LocationId {
+        LocationId::Airport(self.number)
+    }
     fn get_position(&self) -> Point {
         self.position
     }
@@ -39,13 +77,16 @@ impl fmt::Display for Airport {
     }
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, Serialize, Deserialize)]
 #[display(fmt = "*{}", number)]
 pub struct Beacon {
     pub position: Point,
     pub number: i32,
 }
 impl Location for Beacon {
+    fn id(&self) -> LocationId {
+        LocationId::Beacon(self.number)
+    }
     fn get_position(&self) -> Point {
         self.position
     }
@@ -60,7 +101,7 @@ impl Location for Beacon {
     }
 }
 
-#[derive(Display)]
+#[derive(Display, Clone, Serialize, Deserialize)]
 #[display(fmt = "{}", number)]
 pub struct Exit {
     pub position: Point,
@@ -68,7 +109,13 @@ pub struct Exit {
     pub entry_direction: Direction, // Direction that planes use when entering the map from the exit
                                     // Exit direction is opposite to entry direction
 }
+impl Exit {
+    pub const EXIT_ALTITUDE: i32 = 9;
+}
 impl Location for Exit {
+    fn id(&self) -> LocationId {
+        LocationId::Exit(self.number)
+    }
     fn get_position(&self) -> Point {
         self.position
     }
@@ -76,13 +123,14 @@ impl Location for Exit {
         direction == &self.entry_direction || direction == &self.entry_direction.opposite()
     }
     fn can_exit_at_alt(&self, alt: i32) -> bool {
-        alt == 9
+        alt == Self::EXIT_ALTITUDE
     }
     fn to_destination_string(&self) -> String {
         format!("E{}", self.number)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Airway {
     pub start: Point,
     pub length: i32,
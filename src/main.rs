@@ -1,30 +1,53 @@
 mod cli;
-mod command;
-mod command_parser;
-mod direction;
-mod game;
 mod graphics;
 mod interactive_game;
-mod levels;
-mod locations;
-mod plane;
-mod point;
+mod line_editor;
+mod message_log;
+mod replay_viewer;
+mod trails;
 
+use atc::{game, levels, replay, save, scores};
 use pancurses::endwin;
 
 use std::collections::HashMap;
 
 fn main() {
-    let mut level_map: HashMap<_, fn() -> levels::level::Level> = HashMap::new();
-    // Please order the levels alphabetically
-    level_map.insert("Airportcross", levels::airportcross::create);
-    level_map.insert("Default", levels::default::create);
-    level_map.insert("Seattle", levels::seattle::create);
-    level_map.insert("Small", levels::small::create);
+    let mut level_map = levels::level_map();
+    for error in levels::loader::load_level_files(&mut level_map) {
+        eprintln!("{error}");
+    }
 
     let options = cli::parse_args();
 
-    let mut level = match level_map.get(&options.level_name as &str) {
+    if let Some(replay_path) = &options.replay_path {
+        play_replay(replay_path, &level_map, options.trail_length);
+        return;
+    }
+
+    if let Some(bsd_path) = &options.convert_bsd_path {
+        match levels::bsd::load_bsd_level_file(std::path::Path::new(bsd_path)) {
+            Ok(level) => print!("{}", levels::loader::level_to_toml(&level)),
+            Err(e) => println!("{e}"),
+        }
+        return;
+    }
+
+    if options.show_scores || options.scores_level_name.is_some() {
+        print_scores(options.scores_level_name.as_deref());
+        return;
+    }
+
+    if options.validate {
+        let success = validate_level(&options.level_name, &level_map);
+        std::process::exit(if success { 0 } else { 1 });
+    }
+
+    if let Some(load_path) = &options.load_path {
+        play_saved_game(load_path, &options);
+        return;
+    }
+
+    let level = match level_map.get(&options.level_name) {
         Some(factory) => factory(),
         None => {
             println!(
@@ -40,22 +63,189 @@ fn main() {
     } else if options.show_level_list {
         print_level_list(&level_map)
     } else {
-        let mut interactive_game = interactive_game::InteractiveGame::from_level(&mut level);
+        let seed = options.seed.unwrap_or_else(game::Game::random_seed);
+        let recorder = match &options.record_path {
+            Some(path) => match replay::Recorder::create(path, seed, &options.level_name) {
+                Ok(recorder) => Some(recorder),
+                Err(e) => {
+                    println!("{e}");
+                    return;
+                }
+            },
+            None => None,
+        };
+        let mut interactive_game = interactive_game::InteractiveGame::from_level(
+            &options.level_name,
+            level,
+            seed,
+            options.trail_length,
+        );
+        interactive_game.recorder = recorder;
+        interactive_game.player_name = options.player_name.clone();
+        if let Some(save_path) = &options.save_path {
+            interactive_game.save_path = save_path.clone();
+        }
         interactive_game.play();
         endwin();
     }
 }
 
-fn print_description(level_name: &str, level: &crate::levels::level::Level) {
+fn play_saved_game(load_path: &str, options: &cli::Options) {
+    // These options only make sense for a new game, so refuse them rather than ignoring them
+    if options.seed.is_some() {
+        eprintln!(
+            "--seed can't be used with --load, as a saved game keeps the seed it was started with"
+        );
+        std::process::exit(1);
+    }
+    if options.record_path.is_some() {
+        eprintln!(
+            "--record can't be used with --load, as replays have to start from the beginning"
+        );
+        std::process::exit(1);
+    }
+    let (level_name, game) = match save::load_game(load_path) {
+        Ok(saved) => saved,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+    let mut interactive_game =
+        interactive_game::InteractiveGame::from_game(&level_name, game, options.trail_length);
+    // Save back to the same file unless told otherwise
+    interactive_game.save_path = options.save_path.clone().unwrap_or(load_path.to_string());
+    interactive_game.player_name = options.player_name.clone();
+    interactive_game.play();
+    endwin();
+}
+
+fn play_replay(
+    replay_path: &str,
+    level_map: &HashMap<String, levels::LevelFactory>,
+    trail_length: usize,
+) {
+    let replay = match replay::Replay::load(replay_path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+    let level = match level_map.get(&replay.level_name) {
+        Some(factory) => factory(),
+        None => {
+            println!("Replay is of unknown level \"{}\"", replay.level_name);
+            return;
+        }
+    };
+    let mut replay_viewer = replay_viewer::ReplayViewer::new(level, replay, trail_length);
+    replay_viewer.play();
+    endwin();
+}
+
+fn validate_level(level_name: &str, level_map: &HashMap<String, levels::LevelFactory>) -> bool {
+    // Print problems with a level, returning whether it has no errors.
+    // Paths to level files are accepted too, so that levels can be checked before they are installed
+    let path = std::path::Path::new(level_name);
+    let level = if path.is_file() {
+        let is_bsd = path.extension().and_then(|e| e.to_str())
+            == Some(levels::bsd::BSD_LEVEL_FILE_EXTENSION);
+        let loaded = if is_bsd {
+            levels::bsd::load_bsd_level_file(path)
+        } else {
+            levels::loader::load_level_file(path)
+        };
+        match loaded {
+            Ok(level) => level,
+            Err(e) => {
+                println!("error: {e}");
+                return false;
+            }
+        }
+    } else {
+        match level_map.get(level_name) {
+            Some(factory) => factory(),
+            None => {
+                println!(
+                    "error: Unknown level \"{level_name}\". Run with -l option for list of levels"
+                );
+                return false;
+            }
+        }
+    };
+
+    let diagnostics = levels::validator::validate_level(&level);
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+    let error_count = diagnostics
+        .iter()
+        .filter(|d| d.severity == levels::validator::Severity::Error)
+        .count();
+    let warning_count = diagnostics.len() - error_count;
+    println!("{level_name}: {error_count} error(s), {warning_count} warning(s)");
+    error_count == 0
+}
+
+fn print_description(level_name: &str, level: &levels::level::Level) {
     println!("{}", level_name);
     println!("{}\n", "-".repeat(level_name.len()));
     println!("{}", level.description);
 }
 
-fn print_level_list(level_map: &HashMap<&str, fn() -> levels::level::Level>) {
-    let mut level_names: Vec<_> = level_map.keys().map(|x| x.clone()).collect();
+fn print_level_list(level_map: &HashMap<String, levels::LevelFactory>) {
+    let mut level_names: Vec<_> = level_map.keys().map(|name| name.as_str()).collect();
     level_names.sort();
 
     println!("Available levels:");
     println!("{}", level_names.join("\n"));
 }
+
+fn print_scores(level_name: Option<&str>) {
+    // Print the high score table for a level, or for every level that has been played if level_name is None
+    let all_scores = match scores::load_scores(&scores::default_scores_path()) {
+        Ok(all_scores) => all_scores,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+
+    let mut level_names: Vec<_> = match level_name {
+        Some(name) => vec![name],
+        None => all_scores.iter().map(|s| s.level_name.as_str()).collect(),
+    };
+    level_names.sort();
+    level_names.dedup();
+    if level_names.is_empty() {
+        println!("No games have been played yet");
+    }
+
+    for level_name in level_names {
+        println!("{}", level_name);
+        println!("{}", "-".repeat(level_name.len()));
+        let level_scores = scores::ranked_scores(&all_scores, level_name);
+        if level_scores.is_empty() {
+            println!("No games have been played on this level yet");
+        }
+        for (idx, score) in level_scores.iter().enumerate() {
+            let date = chrono::DateTime::from_timestamp(score.timestamp, 0)
+                .map(|d| {
+                    d.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            println!(
+                "{:>3}. {:<16} {:>3} safe {:>5} ticks  {date}  {}",
+                idx + 1,
+                score.player_name,
+                score.planes_safe,
+                score.ticks,
+                score.lose_reason
+            );
+        }
+        println!();
+    }
+}
@@ -0,0 +1,54 @@
+use atc::game_event::GameEvent;
+
+pub struct LogEntry {
+    pub tick: i32,
+    pub text: String,
+}
+
+pub struct MessageLog {
+    // Things that have happened during the game, so that they can be noticed after the moment has passed
+    pub entries: Vec<LogEntry>,
+    pub scroll: usize, // how many entries back from the newest one the view has been scrolled
+}
+
+impl MessageLog {
+    const MAX_ENTRIES: usize = 200;
+
+    pub fn new() -> Self {
+        MessageLog {
+            entries: vec![],
+            scroll: 0,
+        }
+    }
+
+    pub fn add(&mut self, tick: i32, text: String) {
+        self.entries.push(LogEntry { tick, text });
+        if self.entries.len() > Self::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        // Keep showing the same entries if the view has been scrolled back
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.entries.len() - 1);
+        }
+    }
+
+    pub fn add_events(&mut self, tick: i32, events: &[GameEvent]) {
+        for event in events {
+            self.add(tick, event.to_string());
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        // Scrolling is by entry, as long entries take up several lines
+        self.scroll = (self.scroll + 1).min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn visible_entries(&self) -> &[LogEntry] {
+        // The entries up to the newest one that is scrolled to
+        &self.entries[..self.entries.len() - self.scroll]
+    }
+}
@@ -3,16 +3,21 @@ use std::fmt;
 
 use crate::command::*;
 use crate::direction::Direction;
-use crate::locations::{self, Location};
+use crate::game_event::GameEvent;
+use crate::levels::level::Level;
+use crate::locations::LocationId;
 use crate::point::Point;
 
-#[derive(Copy, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PlaneVisibility {
     Marked,   // Fully visible
     Ignored,  // Less visible
     Unmarked, // Like ignored, but becomes marked after processing a delayed command
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum PlaneType {
     Propeller,
     Jet,
@@ -27,12 +32,14 @@ impl PlaneType {
     }
 }
 
-pub enum PlaneState<'a> {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PlaneState {
     Flying,
-    AtAirport(&'a locations::Airport),
+    AtAirport(LocationId),
 }
 
-pub struct Plane<'a> {
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Plane {
     pub name: char,
     pub plane_type: PlaneType,
 
@@ -41,17 +48,17 @@ pub struct Plane<'a> {
     pub direction: Direction,
     pub position: Point,
 
-    pub state: PlaneState<'a>,
+    pub state: PlaneState,
     pub visibility: PlaneVisibility,
     pub ticks_since_created: i32,
     pub remaining_fuel: i32,
 
-    pub destination: &'a dyn Location,
-    pub command_queue: Vec<Command<'a>>,
-    pub command_map: HashMap<CommandType, Command<'a>>,
+    pub destination: LocationId,
+    pub command_queue: Vec<Command>,
+    pub command_map: HashMap<CommandType, Command>,
 }
 
-impl<'a> fmt::Display for Plane<'a> {
+impl fmt::Display for Plane {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let capitalised_name = match self.plane_type {
             PlaneType::Propeller => self.name.to_ascii_uppercase(),
@@ -61,10 +68,10 @@ impl<'a> fmt::Display for Plane<'a> {
     }
 }
 
-impl<'a> Plane<'a> {
-    pub fn fly(&mut self) {
+impl Plane {
+    pub fn fly(&mut self, level: &Level, events: &mut Vec<GameEvent>) {
         if self.ticks_since_created % self.plane_type.get_move_interval() == 0 {
-            self.parse_all_commands();
+            self.parse_all_commands(level, events);
             self.update_altitude();
             if !self.is_at_airport() {
                 self.update_position();
@@ -75,36 +82,123 @@ impl<'a> Plane<'a> {
         self.ticks_since_created += 1;
     }
 
-    pub fn add_command(&mut self, command: Command<'a>) {
-        let command_type = match command {
-            Command::ChangeAltitude(_) => CommandType::ChangeAltitude,
-            Command::Directional(_) => CommandType::Directional,
-            Command::ChangeVisibility(_) => CommandType::ChangeVisibility,
-        };
+    pub fn project(&self, level: &Level, ticks: i32) -> Vec<Plane> {
+        // Copies of the plane after each of the next few ticks if its commands aren't changed.
+        // Stops early if the plane reaches its destination, as it is removed from the game then
+        let mut plane = self.clone();
+        let mut projection = vec![];
+        for _ in 0..ticks {
+            plane.fly(level, &mut vec![]);
+            if plane.is_at_destination(level) {
+                break;
+            }
+            projection.push(plane.clone());
+        }
+        projection
+    }
+
+    pub fn project_path(&self, level: &Level, ticks: i32) -> Vec<Point> {
+        // Where the plane will fly over the next few ticks. Each position is only listed once
+        // even if the plane doesn't move every tick
+        let mut path: Vec<Point> = vec![];
+        for plane in self.project(level, ticks) {
+            let last_position = path.last().unwrap_or(&self.position);
+            if !plane.is_at_airport() && plane.position != *last_position {
+                path.push(plane.position);
+            }
+        }
+        path
+    }
+
+    pub fn add_command(&mut self, command: Command) {
+        // Replace the current command of this type, along with any queued ones
+        let command_type = command.command_type();
+        self.command_queue
+            .retain(|c| c.command_type() != command_type);
         self.command_map.insert(command_type, command);
     }
 
+    pub fn queue_command(&mut self, command: Command) {
+        // Run a command once every command before it has finished
+        self.command_queue.push(command);
+    }
+
+    pub fn cancel_commands(&mut self, command_type: Option<CommandType>) {
+        // Remove current and queued commands of a type, or every command if there is no type
+        match command_type {
+            Some(command_type) => {
+                self.command_map.remove(&command_type);
+                self.command_queue
+                    .retain(|c| c.command_type() != command_type);
+            }
+            None => {
+                self.command_map.clear();
+                self.command_queue.clear();
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        // Full state of the plane, shown when the user queries it
+        let plane_type = match self.plane_type {
+            PlaneType::Propeller => "propeller",
+            PlaneType::Jet => "jet",
+        };
+        let mut pending: Vec<_> = self.command_map.values().map(|c| c.to_string()).collect();
+        pending.sort();
+        let pending = if pending.is_empty() {
+            "none".to_string()
+        } else {
+            pending.join(", ")
+        };
+        let mut text = format!(
+            "{self}: {plane_type} to {}, heading {}, target alt {}, fuel {}\nPending: {pending}",
+            self.destination,
+            self.direction.to_heading(),
+            self.target_altitude,
+            self.remaining_fuel
+        );
+        if !self.command_queue.is_empty() {
+            let queued: Vec<_> = self.command_queue.iter().map(|c| c.to_string()).collect();
+            text += &format!("\nQueued: {}", queued.join(", "));
+        }
+        text
+    }
+
+    pub fn has_pending_command(&self, command_type: CommandType) -> bool {
+        self.command_map.contains_key(&command_type)
+            || self
+                .command_queue
+                .iter()
+                .any(|c| c.command_type() == command_type)
+    }
+
     pub fn get_command(&self, command_type: CommandType) -> Option<&Command> {
         self.command_map.get(&command_type)
     }
 
     pub fn is_colliding_with(&self, other: &Plane) -> bool {
-        (self.altitude - other.altitude).abs() <= 1
+        // Planes waiting at an airport are on the ground, so they can't collide
+        !self.is_at_airport()
+            && !other.is_at_airport()
+            && (self.altitude - other.altitude).abs() <= 1
             && (self.position.x - other.position.x).abs() <= 1
             && (self.position.y - other.position.y).abs() <= 1
     }
 
-    pub fn is_at_destination(&self) -> bool {
-        self.position.equals(&self.destination.get_position())
-            && self.destination.accessible_from_direction(&self.direction)
-            && self.destination.can_exit_at_alt(self.altitude)
+    pub fn is_at_destination(&self, level: &Level) -> bool {
+        match level.get_location(self.destination) {
+            Some(destination) => {
+                self.position.equals(&destination.get_position())
+                    && destination.accessible_from_direction(&self.direction)
+                    && destination.can_exit_at_alt(self.altitude)
+            }
+            None => false,
+        }
     }
 
     pub fn is_at_airport(&self) -> bool {
-        match self.state {
-            PlaneState::AtAirport(_) => true,
-            _ => false,
-        }
+        matches!(self.state, PlaneState::AtAirport(_))
     }
 
     fn update_altitude(&mut self) {
@@ -119,38 +213,52 @@ impl<'a> Plane<'a> {
         self.position = self.position.add(&self.direction.to_point_offset());
     }
 
-    fn parse_all_commands(&mut self) {
+    fn parse_all_commands(&mut self, level: &Level, events: &mut Vec<GameEvent>) {
+        // Queued commands run in order, so the next one only starts when nothing else is pending.
+        // It then waits for its trigger like any other command
+        if self.command_map.is_empty() && !self.command_queue.is_empty() {
+            let command = self.command_queue.remove(0);
+            self.command_map.insert(command.command_type(), command);
+        }
+
         // Very convoluted method of looping over the commands, otherwise we run into borrowing issues.
         // Why must it be so hard to delegate to submethods in rust?
 
-        if self.parse_command(CommandType::Directional) {
+        if self.parse_command(CommandType::Directional, level, events) {
             self.command_map.remove(&CommandType::Directional);
         }
-        if self.parse_command(CommandType::ChangeVisibility) {
+        if self.parse_command(CommandType::ChangeVisibility, level, events) {
             self.command_map.remove(&CommandType::ChangeVisibility);
         }
-        if self.parse_command(CommandType::ChangeAltitude) {
+        if self.parse_command(CommandType::ChangeAltitude, level, events) {
             self.command_map.remove(&CommandType::ChangeAltitude);
         }
     }
 
-    fn parse_command(&mut self, command_type: CommandType) -> bool {
+    fn parse_command(
+        &mut self,
+        command_type: CommandType,
+        level: &Level,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
         let mut command = match self.command_map.get_mut(&command_type) {
             Some(v) => v,
             None => return false,
         };
 
+        // Check if we should do the command now
+        if !is_triggered(command.temporality(), &self.position, self.altitude, level) {
+            return false;
+        }
+        if let CommandTemporality::Delayed(_) = command.temporality() {
+            events.push(GameEvent::CommandTriggered {
+                plane: self.name,
+                command: command.clone(),
+            });
+        }
+
         let should_delete: bool = match &mut command {
             Command::Directional(directional_command) => {
-                // Check if we should do the command now
-                let do_it_now = match directional_command.temporality {
-                    CommandTemporality::Immediate => true,
-                    CommandTemporality::Delayed(l) => l.position.equals(&self.position),
-                };
-                if !do_it_now {
-                    return false;
-                }
-
                 // Now that command has started to be processed, it should continue
                 directional_command.temporality = CommandTemporality::Immediate;
 
@@ -165,11 +273,10 @@ impl<'a> Plane<'a> {
                 }
 
                 // Actually run the command
-                match &directional_command.value {
+                match &mut directional_command.value {
                     DirectionalCommandValue::AbsoluteTurn(direction) => {
-                        self.direction = Direction::limited_rotate(&self.direction, &direction);
-                        let should_delete = self.direction == *direction;
-                        should_delete
+                        self.direction = Direction::limited_rotate(&self.direction, direction);
+                        self.direction == *direction
                     }
                     DirectionalCommandValue::SoftTurn { to_right } => {
                         self.direction = self
@@ -185,16 +292,36 @@ impl<'a> Plane<'a> {
                             .unwrap();
                         true
                     }
-                    DirectionalCommandValue::TurnTowards(location) => {
-                        let position_delta = location.get_position().sub(&self.position);
-                        let target_heading = position_delta.heading().to_degrees();
-                        let target_heading = ((target_heading / 45.).round() * 45.) as i32;
-                        let target_direction = Direction::from_heading(target_heading).unwrap();
+                    DirectionalCommandValue::TurnTowards(id) => {
+                        let location = match level.get_location(*id) {
+                            Some(location) => location,
+                            None => return true,
+                        };
+                        let target_direction =
+                            direction_towards(&self.position, &location.get_position());
                         self.direction =
                             Direction::limited_rotate(&self.direction, &target_direction);
-                        let should_delete = self.direction == target_direction;
-
-                        should_delete
+                        self.direction == target_direction
+                    }
+                    DirectionalCommandValue::Route(waypoints) => {
+                        // Move on to the next waypoint once the current one has been reached
+                        let reached = |id: &LocationId| match level.get_location(*id) {
+                            Some(location) => location.get_position().equals(&self.position),
+                            None => true,
+                        };
+                        if waypoints.first().is_some_and(reached) {
+                            waypoints.remove(0);
+                        }
+                        let location =
+                            match waypoints.first().and_then(|id| level.get_location(*id)) {
+                                Some(location) => location,
+                                None => return true,
+                            };
+                        let target_direction =
+                            direction_towards(&self.position, &location.get_position());
+                        self.direction =
+                            Direction::limited_rotate(&self.direction, &target_direction);
+                        false
                     }
                     DirectionalCommandValue::Circle { to_right } => {
                         self.direction = self
@@ -207,13 +334,15 @@ impl<'a> Plane<'a> {
             }
             Command::ChangeAltitude(altitude_command) => {
                 self.state = PlaneState::Flying;
-                match altitude_command {
-                    ChangeAltitudeCommand::Absolute(altitude) => self.target_altitude = *altitude,
-                    ChangeAltitudeCommand::Climb(amount) => {
-                        self.target_altitude = self.altitude + *amount
+                match altitude_command.value {
+                    ChangeAltitudeCommandValue::Absolute(altitude) => {
+                        self.target_altitude = altitude
+                    }
+                    ChangeAltitudeCommandValue::Climb(amount) => {
+                        self.target_altitude = self.altitude + amount
                     }
-                    ChangeAltitudeCommand::Descend(amount) => {
-                        self.target_altitude = self.altitude - *amount
+                    ChangeAltitudeCommandValue::Descend(amount) => {
+                        self.target_altitude = self.altitude - amount
                     }
                 };
                 true
@@ -227,3 +356,33 @@ impl<'a> Plane<'a> {
         should_delete
     }
 }
+
+fn direction_towards(from: &Point, to: &Point) -> Direction {
+    // Closest of the 8 directions to the direction of a point
+    let position_delta = to.sub(from);
+    let target_heading = position_delta.heading().to_degrees();
+    let target_heading = ((target_heading / 45.).round() * 45.) as i32;
+    Direction::from_heading(target_heading).unwrap()
+}
+
+fn is_triggered(
+    temporality: &CommandTemporality,
+    position: &Point,
+    altitude: i32,
+    level: &Level,
+) -> bool {
+    // Whether a command should be run now, when the plane is at position and altitude
+    match temporality {
+        CommandTemporality::Immediate => true,
+        CommandTemporality::Delayed(Trigger::Location(id)) => match level.get_location(*id) {
+            Some(location) => location.get_position().equals(position),
+            None => false,
+        },
+        CommandTemporality::Delayed(Trigger::Position(trigger_position)) => {
+            trigger_position.equals(position)
+        }
+        CommandTemporality::Delayed(Trigger::Altitude(trigger_altitude)) => {
+            *trigger_altitude == altitude
+        }
+    }
+}
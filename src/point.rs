@@ -2,7 +2,9 @@
 
 use std::f64::consts::{PI, TAU};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -19,15 +21,15 @@ impl Point {
     }
 
     pub fn add(&self, other: &Point) -> Point {
-        return Point::new(self.x + other.x, self.y + other.y);
+        Point::new(self.x + other.x, self.y + other.y)
     }
 
     pub fn sub(&self, other: &Point) -> Point {
-        return Point::new(self.x - other.x, self.y - other.y);
+        Point::new(self.x - other.x, self.y - other.y)
     }
 
     pub fn equals(&self, other: &Point) -> bool {
-        return self.x == other.x && self.y == other.y;
+        self.x == other.x && self.y == other.y
     }
 
     pub fn heading(&self) -> f64 {
@@ -0,0 +1,156 @@
+// Recording and loading of replay files.
+// A replay file is plain text. It starts with a `seed` and a `level` line, followed by one line per accepted command
+// in the form `<tick> <command>`. If the game finished, the last line is `end <tick>`.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+pub struct Replay {
+    pub seed: u64,
+    pub level_name: String,
+    pub commands: Vec<(i32, String)>, // (tick the command was given on, raw command)
+    pub end_tick: Option<i32>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read replay file {path}: {e}"))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        let mut seed = None;
+        let mut level_name = None;
+        let mut commands = vec![];
+        let mut end_tick = None;
+
+        for (line_idx, line) in text.lines().enumerate() {
+            let line_number = line_idx + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or(format!("Line {line_number}: expected a key and a value"))?;
+            match key {
+                "seed" => {
+                    seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Line {line_number}: invalid seed {value}"))?,
+                    )
+                }
+                "level" => level_name = Some(value.to_string()),
+                "end" => end_tick = Some(parse_tick(value, line_number)?),
+                tick => commands.push((parse_tick(tick, line_number)?, value.to_string())),
+            }
+        }
+
+        Ok(Replay {
+            seed: seed.ok_or("Replay file has no seed")?,
+            level_name: level_name.ok_or("Replay file has no level")?,
+            commands,
+            end_tick,
+        })
+    }
+
+    pub fn commands_at(&self, tick: i32) -> impl Iterator<Item = &String> {
+        // Commands that were given on a tick, in the order they were given
+        self.commands
+            .iter()
+            .filter(move |(command_tick, _)| *command_tick == tick)
+            .map(|(_, raw_command)| raw_command)
+    }
+}
+
+fn parse_tick(text: &str, line_number: usize) -> Result<i32, String> {
+    text.parse()
+        .map_err(|_| format!("Line {line_number}: invalid tick {text}"))
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64, level_name: &str) -> Result<Recorder, String> {
+        let file =
+            File::create(path).map_err(|e| format!("Could not create replay file {path}: {e}"))?;
+        let mut recorder = Recorder {
+            writer: BufWriter::new(file),
+        };
+        recorder.write_line(&format!("seed {seed}"));
+        recorder.write_line(&format!("level {level_name}"));
+        Ok(recorder)
+    }
+
+    pub fn record_command(&mut self, tick: i32, command: &str) {
+        self.write_line(&format!("{tick} {command}"));
+    }
+
+    pub fn record_end(&mut self, tick: i32) {
+        self.write_line(&format!("end {tick}"));
+    }
+
+    fn write_line(&mut self, line: &str) {
+        // Flush after every line so that the recording survives the game being killed.
+        // A failed write shouldn't stop the game so errors are ignored.
+        let _ = writeln!(self.writer, "{line}").and_then(|_| self.writer.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_replay() {
+        let replay = Replay::parse("seed 42\nlevel Default\n3 aa5\n\n10 btw\nend 25\n").unwrap();
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.level_name, "Default");
+        assert_eq!(
+            replay.commands,
+            vec![(3, "aa5".to_string()), (10, "btw".to_string())]
+        );
+        assert_eq!(replay.end_tick, Some(25));
+    }
+
+    #[test]
+    fn rejects_invalid_replays() {
+        assert!(Replay::parse("level Default\n").is_err());
+        assert!(Replay::parse("seed 42\n").is_err());
+        assert!(Replay::parse("seed 42\nlevel Default\nsoon aa5\n").is_err());
+    }
+
+    #[test]
+    fn recorded_replay_loads() {
+        let path = std::env::temp_dir().join(format!("atc-test-{}.replay", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut recorder = Recorder::create(path, 1234, "Small").unwrap();
+        recorder.record_command(0, "ac");
+        recorder.record_command(7, "bt@b1");
+        recorder.record_end(12);
+        drop(recorder);
+
+        let replay = Replay::load(path);
+        let _ = std::fs::remove_file(path);
+        let replay = replay.unwrap();
+        assert_eq!(replay.seed, 1234);
+        assert_eq!(replay.level_name, "Small");
+        assert_eq!(
+            replay.commands,
+            vec![(0, "ac".to_string()), (7, "bt@b1".to_string())]
+        );
+        assert_eq!(replay.end_tick, Some(12));
+    }
+
+    #[test]
+    fn finds_commands_for_tick() {
+        let replay = Replay::parse("seed 1\nlevel Default\n0 aa1\n0 ac\n4 btw\n").unwrap();
+        let commands_at = |tick| replay.commands_at(tick).cloned().collect::<Vec<_>>();
+        assert_eq!(commands_at(0), vec!["aa1", "ac"]);
+        assert!(commands_at(2).is_empty());
+        assert_eq!(commands_at(4), vec!["btw"]);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::graphics::{self, DisplayState, GraphicsContext};
+use crate::message_log::MessageLog;
+use crate::trails::Trails;
+use atc::game::{Conflict, Game, LoseCondition};
+use atc::levels::level::Level;
+use atc::replay::Replay;
+
+use pancurses::Input;
+
+pub struct ReplayViewer {
+    // Plays back a recorded game through a real Game, drawing it with the normal windows but without taking commands
+    pub game: Game,
+    replay: Replay,
+    graphics_context: GraphicsContext,
+    paused: bool,
+    speed: f64,
+    last_command: String,
+    message_log: MessageLog,
+    conflicts: Vec<Conflict>,
+    trails: Trails,
+    frame_count: i32,
+}
+
+impl ReplayViewer {
+    const FRAME_INTERVAL: i32 = 30;
+    const MIN_SPEED: f64 = 0.25;
+    const MAX_SPEED: f64 = 16.;
+
+    pub fn new(level: Level, replay: Replay, trail_length: usize) -> Self {
+        let game = Game::new(level, replay.seed);
+        let graphics_context = graphics::initialize(&game);
+        let conflicts = game.predict_conflicts(Game::CONFLICT_LOOKAHEAD_TICKS);
+        let mut trails = Trails::new(trail_length);
+        trails.update(&game, &[]);
+        ReplayViewer {
+            game,
+            replay,
+            graphics_context,
+            paused: false,
+            speed: 1.,
+            last_command: "".to_string(),
+            message_log: MessageLog::new(),
+            conflicts,
+            trails,
+            frame_count: 0,
+        }
+    }
+
+    pub fn play(&mut self) {
+        let mut result: Option<LoseCondition> = None;
+        loop {
+            let mut step = false;
+            match self.graphics_context.stdscr.getch() {
+                Some(Input::Character(c)) => match c {
+                    ' ' => self.paused = !self.paused,
+                    's' | '.' => step = self.paused,
+                    '+' | '=' => self.speed = (self.speed * 2.).min(Self::MAX_SPEED),
+                    '-' => self.speed = (self.speed / 2.).max(Self::MIN_SPEED),
+                    'q' => return,
+                    _ => (),
+                },
+                Some(Input::KeyPPage) => self.message_log.scroll_up(),
+                Some(Input::KeyNPage) => self.message_log.scroll_down(),
+                _ => (),
+            }
+
+            let frames_per_tick = ((self.game.level.move_interval * Self::FRAME_INTERVAL as f64)
+                / self.speed)
+                .max(1.) as i32;
+            if !self.paused {
+                self.frame_count += 1;
+            }
+            let finished = result.is_some() || self.reached_end();
+            if !finished && (step || self.frame_count >= frames_per_tick) {
+                self.frame_count = 0;
+                self.apply_commands_for_tick();
+                match self.game.tick() {
+                    Ok(events) => {
+                        self.message_log.add_events(self.game.ticks, &events);
+                        self.trails.update(&self.game, &events);
+                    }
+                    Err(e) => result = Some(e),
+                }
+                self.conflicts = self.game.predict_conflicts(Game::CONFLICT_LOOKAHEAD_TICKS);
+            }
+
+            let status = self.status_text(&result);
+            graphics::draw(
+                &self.game,
+                &self.graphics_context,
+                &DisplayState::from_text(&status),
+                &self.message_log,
+                &self.conflicts,
+                &self.trails,
+            );
+
+            std::thread::sleep(std::time::Duration::from_millis(
+                Self::FRAME_INTERVAL as u64,
+            ));
+        }
+    }
+
+    fn apply_commands_for_tick(&mut self) {
+        // Commands are recorded against the tick number they were given on, so they need to be applied before the next tick
+        for raw_command in self.replay.commands_at(self.game.ticks) {
+            // Only accepted commands are recorded, so these should never fail unless the replay is from a different version
+            let _ = self.game.apply_command(raw_command);
+            self.last_command = raw_command.clone();
+        }
+    }
+
+    fn reached_end(&self) -> bool {
+        match self.replay.end_tick {
+            Some(end_tick) => self.game.ticks >= end_tick,
+            None => false,
+        }
+    }
+
+    fn status_text(&self, result: &Option<LoseCondition>) -> String {
+        let state = match result {
+            Some(e) => format!("{e}. Replay finished"),
+            None if self.reached_end() => "Replay finished".to_string(),
+            None if self.paused => "Paused".to_string(),
+            None => "Playing".to_string(),
+        };
+        format!(
+            "Replay of {} (seed {}) - {state}\nSpeed: x{}    Last command: {}\nspace: pause, s: step, +/-: speed, q: quit",
+            self.replay.level_name, self.replay.seed, self.speed, self.last_command
+        )
+    }
+}
@@ -0,0 +1,77 @@
+// Saving and resuming of games in progress.
+// Save files are JSON containing the name of the level and the full state of the game, including the rng,
+// so a resumed game continues exactly as it would have.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use crate::game::Game;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile<G> {
+    level_name: String,
+    game: G,
+}
+
+pub fn save_game(path: &str, level_name: &str, game: &Game) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Could not create save file {path}: {e}"))?;
+    let save_file = SaveFile {
+        level_name: level_name.to_string(),
+        game,
+    };
+    serde_json::to_writer(BufWriter::new(file), &save_file)
+        .map_err(|e| format!("Could not write save file {path}: {e}"))
+}
+
+pub fn load_game(path: &str) -> Result<(String, Game), String> {
+    // Returns the name of the level and the game
+    let file = File::open(path).map_err(|e| format!("Could not open save file {path}: {e}"))?;
+    let save_file: SaveFile<Game> = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Could not read save file {path}: {e}"))?;
+    Ok((save_file.level_name, save_file.game))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::default;
+
+    fn describe_tick(game: &mut Game) -> String {
+        // Everything that a tick changes that a player could see
+        let result = match game.tick() {
+            Ok(_) => "ok".to_string(),
+            Err(lose_condition) => lose_condition.to_string(),
+        };
+        let planes: Vec<_> = game
+            .planes
+            .iter()
+            .map(|p| format!("{p} {},{} {}", p.position.x, p.position.y, p.destination))
+            .collect();
+        format!("{}: {result} | {}", game.ticks, planes.join(", "))
+    }
+
+    #[test]
+    fn loaded_game_continues_the_same() {
+        let mut game = Game::new(default::create(), 7);
+        game.level.plane_spawn_chance = 0.5;
+        for _ in 0..3 {
+            let _ = game.tick();
+        }
+
+        let path = std::env::temp_dir().join(format!("atc-test-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_game(path, "Default", &game).unwrap();
+        let loaded = load_game(path);
+        let _ = std::fs::remove_file(path);
+        let (level_name, mut loaded_game) = loaded.unwrap();
+        assert_eq!(level_name, "Default");
+        assert_eq!(loaded_game.seed, 7);
+
+        // The rng is saved too, so the same planes should spawn in both
+        for _ in 0..40 {
+            assert_eq!(describe_tick(&mut game), describe_tick(&mut loaded_game));
+        }
+    }
+}
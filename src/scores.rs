@@ -0,0 +1,150 @@
+// Persistent high score table, kept in a JSON file in the user's config directory.
+// Scores are ranked per level, by planes landed safely and then by how quickly they were landed.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::game::{Game, LoseCondition};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub level_name: String,
+    pub player_name: String,
+    pub planes_safe: i32,
+    pub ticks: i32,
+    pub lose_reason: String,
+    pub timestamp: i64, // unix time, in seconds
+}
+
+impl Score {
+    pub fn from_game(
+        game: &Game,
+        level_name: &str,
+        player_name: &str,
+        lose_condition: &LoseCondition,
+    ) -> Score {
+        Score {
+            level_name: level_name.to_string(),
+            player_name: player_name.to_string(),
+            planes_safe: game.planes_safe,
+            ticks: game.ticks,
+            lose_reason: lose_condition.to_string(),
+            timestamp: chrono::Local::now().timestamp(),
+        }
+    }
+}
+
+pub fn default_scores_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or(PathBuf::from("."))
+        .join("atc")
+        .join("scores.json")
+}
+
+pub fn load_scores(path: &Path) -> Result<Vec<Score>, String> {
+    // A missing file just means that nobody has played yet
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let file = File::open(path)
+        .map_err(|e| format!("Could not open scores file {}: {e}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Could not read scores file {}: {e}", path.display()))
+}
+
+pub fn add_score(path: &Path, score: Score) -> Result<(), String> {
+    let mut scores = load_scores(path)?;
+    scores.push(score);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Could not create directory {}: {e}", parent.display()))?;
+    }
+    let file = File::create(path)
+        .map_err(|e| format!("Could not create scores file {}: {e}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &scores)
+        .map_err(|e| format!("Could not write scores file {}: {e}", path.display()))
+}
+
+pub fn ranked_scores<'a>(scores: &'a [Score], level_name: &str) -> Vec<&'a Score> {
+    // Scores for a level, best first
+    let mut level_scores: Vec<_> = scores
+        .iter()
+        .filter(|s| s.level_name == level_name)
+        .collect();
+    level_scores.sort_by_key(|s| (-s.planes_safe, s.ticks));
+    level_scores
+}
+
+pub fn rank_of(scores: &[Score], score: &Score) -> usize {
+    // Position (starting from 1) that a score has in its level's table
+    ranked_scores(scores, &score.level_name)
+        .iter()
+        .filter(|s| (-s.planes_safe, s.ticks) < (-score.planes_safe, score.ticks))
+        .count()
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(level_name: &str, player_name: &str, planes_safe: i32, ticks: i32) -> Score {
+        Score {
+            level_name: level_name.to_string(),
+            player_name: player_name.to_string(),
+            planes_safe,
+            ticks,
+            lose_reason: "".to_string(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn ranks_by_planes_landed_then_ticks() {
+        let scores = vec![
+            score("Default", "slow", 5, 300),
+            score("Default", "few", 2, 100),
+            score("Small", "other level", 9, 50),
+            score("Default", "fast", 5, 200),
+            score("Default", "most", 7, 900),
+        ];
+        let ranked: Vec<_> = ranked_scores(&scores, "Default")
+            .iter()
+            .map(|s| s.player_name.as_str())
+            .collect();
+        assert_eq!(ranked, vec!["most", "fast", "slow", "few"]);
+    }
+
+    #[test]
+    fn finds_rank_of_a_score() {
+        let scores = vec![
+            score("Default", "a", 5, 300),
+            score("Default", "b", 5, 200),
+            score("Small", "c", 9, 50),
+        ];
+        assert_eq!(rank_of(&scores, &score("Default", "new", 6, 999)), 1);
+        assert_eq!(rank_of(&scores, &score("Default", "new", 5, 250)), 2);
+        assert_eq!(rank_of(&scores, &score("Default", "new", 1, 10)), 3);
+        // Scores from other levels don't count
+        assert_eq!(rank_of(&scores, &score("Small", "new", 9, 60)), 2);
+    }
+
+    #[test]
+    fn scores_are_saved() {
+        let path =
+            std::env::temp_dir().join(format!("atc-test-{}-scores.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(load_scores(&path).unwrap().len(), 0);
+        add_score(&path, score("Default", "a", 5, 300)).unwrap();
+        add_score(&path, score("Default", "b", 6, 300)).unwrap();
+        let loaded = load_scores(&path);
+        let _ = fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(ranked_scores(&loaded, "Default")[0].player_name, "b");
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{HashMap, VecDeque};
+
+use atc::game::Game;
+use atc::game_event::GameEvent;
+use atc::point::Point;
+
+pub struct Trails {
+    // The last few positions of each flying plane, oldest first, so that they can be drawn behind it.
+    // This is only for display, so it is kept by the front end instead of in the game
+    pub length: usize,
+    positions: HashMap<char, VecDeque<Point>>,
+}
+
+impl Trails {
+    pub const DEFAULT_LENGTH: usize = 3;
+
+    pub fn new(length: usize) -> Self {
+        Trails {
+            length,
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self, game: &Game, events: &[GameEvent]) {
+        // Call after each tick with the events it returned. The current position of each plane is kept at the back
+        // so that it can be compared with the next one, which is why each trail holds one more position than is drawn
+        for event in events {
+            // Names are reused, so a plane that has left mustn't pass its trail on to the next plane with its name
+            match event {
+                GameEvent::PlaneSpawned { plane, .. }
+                | GameEvent::PlaneLanded { plane, .. }
+                | GameEvent::PlaneExited { plane, .. } => {
+                    self.positions.remove(plane);
+                }
+                _ => (),
+            }
+        }
+        for plane in &game.planes {
+            if plane.is_at_airport() {
+                self.positions.remove(&plane.name);
+                continue;
+            }
+            let trail = self.positions.entry(plane.name).or_default();
+            if trail.back() != Some(&plane.position) {
+                trail.push_back(plane.position);
+            }
+            while trail.len() > self.length + 1 {
+                trail.pop_front();
+            }
+        }
+    }
+
+    pub fn get(&self, plane_name: char) -> impl Iterator<Item = &Point> {
+        // Previous positions of a plane, not including where it is now
+        let trail = self.positions.get(&plane_name);
+        let len = trail.map(|t| t.len()).unwrap_or(0);
+        trail.into_iter().flatten().take(len.saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atc::direction::Direction;
+    use atc::levels::default;
+    use atc::locations::LocationId;
+    use atc::plane::PlaneState;
+
+    #[test]
+    fn trails_are_not_passed_on_to_new_planes() {
+        // Plane a flies east for a few ticks
+        let mut game = Game::new(default::create(), 0);
+        let plane = &mut game.planes[0];
+        plane.name = 'a';
+        plane.state = PlaneState::Flying;
+        plane.position = Point::new(5, 10);
+        plane.direction = Direction::East;
+
+        let mut trails = Trails::new(2);
+        trails.update(&game, &[]);
+        for x in 6..=8 {
+            game.planes[0].position = Point::new(x, 10);
+            trails.update(&game, &[]);
+        }
+        let trail: Vec<_> = trails.get('a').copied().collect();
+        assert_eq!(trail, vec![Point::new(6, 10), Point::new(7, 10)]);
+
+        // It exits, and a new plane a enters right next to where it was
+        game.planes[0].position = Point::new(9, 10);
+        let events = [
+            GameEvent::PlaneExited {
+                plane: 'a',
+                exit: LocationId::Exit(0),
+            },
+            GameEvent::PlaneSpawned {
+                plane: 'a',
+                location: LocationId::Exit(1),
+            },
+        ];
+        trails.update(&game, &events);
+        assert_eq!(trails.get('a').count(), 0);
+    }
+}